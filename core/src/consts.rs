@@ -0,0 +1,12 @@
+// same value as the DEX's MAX_PERCENT, fees are expressed in basis points
+pub const MAX_PERCENT: u64 = 10_000;
+pub const STABLE_SWAP_COINS: u64 = 2;
+pub const STABLE_SWAP_MAX_ITERATIONS: usize = 255;
+// stable swap reserves are compared with this many decimals, whatever the decimals of the tokens
+pub const STABLE_SWAP_PRECISION: u32 = 18;
+// output units kept out of the stable swap amount in: 1 for the output reserve margin and 1 for solving y up to 1
+pub const STABLE_AMOUNT_IN_MARGIN: u64 = 2;
+pub const WAD: u64 = 1_000_000_000_000_000_000;
+pub const LN2_WAD: u64 = 693_147_180_559_945_309;
+pub const MAX_POW_RELATIVE_ERROR: u64 = 10_000;
+pub const WEIGHTED_INVARIANT_TOLERANCE: u64 = 1_000;
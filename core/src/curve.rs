@@ -0,0 +1,92 @@
+use crate::{consts::*, stable_swap::*, weighted::*, MathError, Uint};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Curve {
+    ConstantProduct,
+    StableSwap,
+    Weighted,
+}
+
+// the parameters of a pair's curve, oriented in the direction of a swap
+// rates bring the reserves to a common precision, only the stable swap math uses them
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CurveParams {
+    pub curve: Curve,
+    pub amp: u64,
+    pub weight_in: u64,
+    pub weight_out: u64,
+    pub rate_in: u64,
+    pub rate_out: u64,
+}
+
+pub fn get_amount_out_no_fee<T: Uint>(
+    amount_in: &T,
+    liquidity_in: &T,
+    liquidity_out: &T,
+    params: &CurveParams,
+) -> Result<T, MathError> {
+    match params.curve {
+        Curve::ConstantProduct => {
+            let numerator = amount_in.mul(liquidity_out);
+            let denominator = liquidity_in.add(amount_in);
+
+            Ok(numerator.div(&denominator))
+        }
+        Curve::StableSwap => Ok(get_stable_amount_out(amount_in, liquidity_in, liquidity_out, params)),
+        Curve::Weighted => get_weighted_amount_out(amount_in, liquidity_in, liquidity_out, params.weight_in, params.weight_out),
+    }
+}
+
+pub fn get_amount_in_no_fee<T: Uint>(
+    amount_out: &T,
+    liquidity_in: &T,
+    liquidity_out: &T,
+    params: &CurveParams,
+) -> Result<T, MathError> {
+    if amount_out >= liquidity_out {
+        return Err(MathError::NotEnoughLiquidity);
+    }
+
+    match params.curve {
+        Curve::ConstantProduct => {
+            let numerator = liquidity_in.mul(amount_out);
+            let denominator = liquidity_out.sub(amount_out);
+
+            Ok(numerator.div(&denominator).add_u64(1))
+        }
+        Curve::StableSwap => get_stable_amount_in(amount_out, liquidity_in, liquidity_out, params),
+        Curve::Weighted => get_weighted_amount_in(amount_out, liquidity_in, liquidity_out, params.weight_in, params.weight_out),
+    }
+}
+
+// reserves after a swap should never be worth less than before, according to the pair's curve
+pub fn check_swap_invariant<T: Uint>(
+    liquidity_in: &T,
+    liquidity_out: &T,
+    new_liquidity_in: &T,
+    new_liquidity_out: &T,
+    params: &CurveParams,
+) -> bool {
+    match params.curve {
+        Curve::ConstantProduct => new_liquidity_in.mul(new_liquidity_out) >= liquidity_in.mul(liquidity_out),
+        // D is only computed up to a difference of 1
+        Curve::StableSwap => {
+            get_stable_invariant(new_liquidity_in, new_liquidity_out, params).add_u64(1)
+                >= get_stable_invariant(liquidity_in, liquidity_out, params)
+        }
+        // compared as weight_in * ln(x) + weight_out * ln(y), up to the precision of the logarithm
+        Curve::Weighted => {
+            get_weighted_invariant_ln(new_liquidity_in, new_liquidity_out, params).add_u64(WEIGHTED_INVARIANT_TOLERANCE)
+                >= get_weighted_invariant_ln(liquidity_in, liquidity_out, params)
+        }
+    }
+}
+
+pub fn get_weighted_invariant_ln<T: Uint>(
+    liquidity_in: &T,
+    liquidity_out: &T,
+    params: &CurveParams,
+) -> T {
+    ln_wad(&liquidity_in.mul_u64(WAD)).mul_u64(params.weight_in)
+        .add(&ln_wad(&liquidity_out.mul_u64(WAD)).mul_u64(params.weight_out))
+}
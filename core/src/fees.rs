@@ -0,0 +1,28 @@
+use crate::{consts::MAX_PERCENT, Uint};
+
+// returns lp fee, owner fee, total fee calculated from amount
+pub fn get_fee_amounts<T: Uint>(
+    amount: &T,
+    is_input: bool,
+    lp_fee: u64,
+    owner_fee: u64,
+) -> (T, T, T) {
+    let total_fee = lp_fee + owner_fee;
+    if total_fee == 0 {
+        return (T::zero(), T::zero(), T::zero());
+    }
+
+    if is_input {
+        let total_fee_amount = amount.mul_u64(total_fee).div_u64(MAX_PERCENT);
+        let owner_fee_amount = amount.mul_u64(owner_fee).div_u64(MAX_PERCENT);
+        let lp_fee_amount = total_fee_amount.sub(&owner_fee_amount);
+
+        (lp_fee_amount, owner_fee_amount, total_fee_amount)
+    } else {
+        let total_fee_amount = amount.mul_u64(total_fee).div_u64(MAX_PERCENT - total_fee);
+        let lp_fee_amount = total_fee_amount.mul_u64(lp_fee).div_u64(total_fee);
+        let owner_fee_amount = total_fee_amount.sub(&lp_fee_amount);
+
+        (lp_fee_amount, owner_fee_amount, total_fee_amount)
+    }
+}
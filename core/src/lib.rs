@@ -0,0 +1,28 @@
+#![no_std]
+
+//! Pricing math of the Test DEX, shared by the contract and the off-chain simulator.
+//!
+//! Every function is generic over [`Uint`], so the same code runs on the VM's managed big integers
+//! and on `num-bigint` integers, producing identical results.
+
+pub mod consts;
+pub mod curve;
+pub mod fees;
+pub mod liquidity;
+pub mod stable_swap;
+pub mod swap;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod uint;
+pub mod weighted;
+
+pub use curve::{Curve, CurveParams};
+pub use swap::SwapResult;
+pub use uint::Uint;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MathError {
+    PowOverflow,
+    InvariantViolated,
+    NotEnoughLiquidity,
+}
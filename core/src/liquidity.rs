@@ -0,0 +1,69 @@
+use crate::{consts::MAX_PERCENT, curve::Curve, stable_swap::compute_d, Uint};
+
+// deposits are always proportional to the reserves, so adding liquidity does not move the price, whatever the curve
+pub fn quote<T: Uint>(token_amount: &T, token_liquidity: &T, base_liquidity: &T) -> T {
+    token_amount.mul(base_liquidity).div(token_liquidity)
+}
+
+// LP tokens minted for the first deposit of a pair, the rates bringing stable swap amounts to a common precision
+pub fn get_initial_lp_amount<T: Uint>(
+    token_amount: &T,
+    base_amount: &T,
+    curve: Curve,
+    amp: u64,
+    base_weight: u64,
+    token_rate: u64,
+    base_rate: u64,
+) -> T {
+    match curve {
+        Curve::ConstantProduct => base_amount.clone(),
+        Curve::StableSwap => compute_d(&token_amount.mul_u64(token_rate), &base_amount.mul_u64(base_rate), amp),
+        Curve::Weighted => base_amount.mul_u64(MAX_PERCENT).div_u64(base_weight),
+    }
+}
+
+// the part of the deposited amounts matching the current reserves ratio
+pub fn get_optimal_amounts<T: Uint>(
+    token_amount: &T,
+    base_amount: &T,
+    liquidity_token: &T,
+    liquidity_base: &T,
+) -> (T, T) {
+    let base_optimal = quote(token_amount, liquidity_token, liquidity_base);
+    if &base_optimal < base_amount {
+        (token_amount.clone(), base_optimal)
+    } else {
+        let token_optimal = quote(base_amount, liquidity_base, liquidity_token);
+
+        (token_optimal, base_amount.clone())
+    }
+}
+
+pub fn get_lp_amount<T: Uint>(
+    token_amount: &T,
+    base_amount: &T,
+    liquidity_token: &T,
+    liquidity_base: &T,
+    lp_supply: &T,
+) -> T {
+    let first_potential_lp = token_amount.mul(lp_supply).div(liquidity_token);
+    let second_potential_lp = base_amount.mul(lp_supply).div(liquidity_base);
+    if first_potential_lp < second_potential_lp {
+        first_potential_lp
+    } else {
+        second_potential_lp
+    }
+}
+
+// returns the token and base token amounts redeemed by lp_amount
+pub fn get_removed_amounts<T: Uint>(
+    lp_amount: &T,
+    liquidity_token: &T,
+    liquidity_base: &T,
+    lp_supply: &T,
+) -> (T, T) {
+    (
+        liquidity_token.mul(lp_amount).div(lp_supply),
+        liquidity_base.mul(lp_amount).div(lp_supply),
+    )
+}
@@ -0,0 +1,105 @@
+use crate::{consts::*, CurveParams, MathError, Uint};
+
+// StableSwap invariant for two coins: A*n^n*(x+y) + D = A*D*n^n + D^(n+1)/(n^n*x*y)
+pub fn compute_d<T: Uint>(x: &T, y: &T, amp: u64) -> T {
+    if x.is_zero() || y.is_zero() {
+        return T::zero();
+    }
+
+    let sum = x.add(y);
+    let ann = amp * STABLE_SWAP_COINS;
+    let mut d = sum.clone();
+    for _ in 0..STABLE_SWAP_MAX_ITERATIONS {
+        let mut d_p = d.clone();
+        d_p = d_p.mul(&d).div(&x.mul_u64(STABLE_SWAP_COINS));
+        d_p = d_p.mul(&d).div(&y.mul_u64(STABLE_SWAP_COINS));
+
+        let d_prev = d.clone();
+        let numerator = sum.mul_u64(ann).add(&d_p.mul_u64(STABLE_SWAP_COINS)).mul(&d);
+        let denominator = d.mul_u64(ann - 1).add(&d_p.mul_u64(STABLE_SWAP_COINS + 1));
+        d = numerator.div(&denominator);
+        if within_one(&d, &d_prev) {
+            break;
+        }
+    }
+
+    d
+}
+
+// solves the invariant for the other reserve, given one reserve and D
+pub fn compute_y<T: Uint>(x: &T, d: &T, amp: u64) -> T {
+    let ann = amp * STABLE_SWAP_COINS;
+    let mut c = d.mul(d).div(&x.mul_u64(STABLE_SWAP_COINS));
+    c = c.mul(d).div_u64(ann * STABLE_SWAP_COINS);
+    let b = x.add(&d.div_u64(ann));
+
+    let mut y = d.clone();
+    for _ in 0..STABLE_SWAP_MAX_ITERATIONS {
+        let y_prev = y.clone();
+        let numerator = y.mul(&y).add(&c);
+        let denominator = y.mul_u64(2).add(&b).sub(d);
+        y = numerator.div(&denominator);
+        if within_one(&y, &y_prev) {
+            break;
+        }
+    }
+
+    y
+}
+
+// multiplier bringing amounts of a token with the given decimals to STABLE_SWAP_PRECISION
+pub fn get_precision_rate(decimals: u32) -> u64 {
+    10u64.pow(STABLE_SWAP_PRECISION.saturating_sub(decimals))
+}
+
+// the invariant of reserves brought to a common precision, so that tokens with different decimals can be pegged
+pub fn get_stable_invariant<T: Uint>(liquidity_in: &T, liquidity_out: &T, params: &CurveParams) -> T {
+    compute_d(&liquidity_in.mul_u64(params.rate_in), &liquidity_out.mul_u64(params.rate_out), params.amp)
+}
+
+pub fn get_stable_amount_out<T: Uint>(
+    amount_in: &T,
+    liquidity_in: &T,
+    liquidity_out: &T,
+    params: &CurveParams,
+) -> T {
+    let x = liquidity_in.mul_u64(params.rate_in);
+    let y = liquidity_out.mul_u64(params.rate_out);
+    let d = compute_d(&x, &y, params.amp);
+    let new_y = compute_y(&x.add(&amount_in.mul_u64(params.rate_in)), &d, params.amp).add_u64(1);
+    if new_y >= y {
+        return T::zero();
+    }
+
+    y.sub(&new_y).div_u64(params.rate_out)
+}
+
+pub fn get_stable_amount_in<T: Uint>(
+    amount_out: &T,
+    liquidity_in: &T,
+    liquidity_out: &T,
+    params: &CurveParams,
+) -> Result<T, MathError> {
+    // the input is computed for a few more output units, to always buy at least amount_out
+    let left_y = liquidity_out.sub(amount_out).mul_u64(params.rate_out);
+    if left_y <= T::from_u64(STABLE_AMOUNT_IN_MARGIN) {
+        return Err(MathError::NotEnoughLiquidity);
+    }
+
+    let x = liquidity_in.mul_u64(params.rate_in);
+    let d = compute_d(&x, &liquidity_out.mul_u64(params.rate_out), params.amp);
+    let new_x = compute_y(&left_y.sub_u64(STABLE_AMOUNT_IN_MARGIN), &d, params.amp);
+    if new_x <= x {
+        return Ok(T::from_u64(1));
+    }
+
+    Ok(new_x.sub(&x).div_u64(params.rate_in).add_u64(1))
+}
+
+fn within_one<T: Uint>(a: &T, b: &T) -> bool {
+    if a > b {
+        a.sub(b) <= T::from_u64(1)
+    } else {
+        b.sub(a) <= T::from_u64(1)
+    }
+}
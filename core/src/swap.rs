@@ -0,0 +1,123 @@
+use crate::{curve::*, fees::get_fee_amounts, MathError, Uint};
+
+// the amount paid or received by the user, the pair's new reserves and the fees, owner's being paid out of the pair
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SwapResult<T> {
+    pub amount: T,
+    pub new_liquidity_in: T,
+    pub new_liquidity_out: T,
+    pub lp_fee: T,
+    pub owner_fee: T,
+}
+
+// quotes go through the swap math, so they always match the amounts of the actual swap
+pub fn get_amount_out<T: Uint>(
+    amount_in: &T,
+    liquidity_in: &T,
+    liquidity_out: &T,
+    fee_in: bool,
+    lp_fee: u64,
+    owner_fee: u64,
+    params: &CurveParams,
+) -> Result<T, MathError> {
+    do_swap_fixed_input(amount_in, liquidity_in, liquidity_out, fee_in, lp_fee, owner_fee, params)
+        .map(|result| result.amount)
+}
+
+pub fn get_amount_in<T: Uint>(
+    amount_out: &T,
+    liquidity_in: &T,
+    liquidity_out: &T,
+    fee_in: bool,
+    lp_fee: u64,
+    owner_fee: u64,
+    params: &CurveParams,
+) -> Result<T, MathError> {
+    do_swap_fixed_output(amount_out, liquidity_in, liquidity_out, fee_in, lp_fee, owner_fee, params)
+        .map(|result| result.amount)
+}
+
+pub fn do_swap_fixed_input<T: Uint>(
+    amount_in: &T,
+    liquidity_in: &T,
+    liquidity_out: &T,
+    fee_in: bool,
+    lp_fee: u64,
+    owner_fee: u64,
+    params: &CurveParams,
+) -> Result<SwapResult<T>, MathError> {
+    let result = if fee_in {
+        let (lp_fee, owner_fee, total_fee) = get_fee_amounts(amount_in, true, lp_fee, owner_fee);
+        let left_amount_in = amount_in.sub(&total_fee);
+        let amount_out = get_amount_out_no_fee(&left_amount_in, liquidity_in, liquidity_out, params)?;
+
+        SwapResult {
+            new_liquidity_in: liquidity_in.add(&left_amount_in).add(&lp_fee),
+            new_liquidity_out: liquidity_out.sub(&amount_out),
+            amount: amount_out,
+            lp_fee,
+            owner_fee,
+        }
+    } else {
+        let amount_out = get_amount_out_no_fee(amount_in, liquidity_in, liquidity_out, params)?;
+        let (lp_fee, owner_fee, total_fee) = get_fee_amounts(&amount_out, true, lp_fee, owner_fee);
+
+        SwapResult {
+            amount: amount_out.sub(&total_fee),
+            new_liquidity_in: liquidity_in.add(amount_in),
+            new_liquidity_out: liquidity_out.sub(&amount_out).add(&lp_fee),
+            lp_fee,
+            owner_fee,
+        }
+    };
+    check_result(liquidity_in, liquidity_out, result, params)
+}
+
+pub fn do_swap_fixed_output<T: Uint>(
+    amount_out: &T,
+    liquidity_in: &T,
+    liquidity_out: &T,
+    fee_in: bool,
+    lp_fee: u64,
+    owner_fee: u64,
+    params: &CurveParams,
+) -> Result<SwapResult<T>, MathError> {
+    let result = if fee_in {
+        let amount_in_no_fee = get_amount_in_no_fee(amount_out, liquidity_in, liquidity_out, params)?;
+        let (lp_fee, owner_fee, total_fee) = get_fee_amounts(&amount_in_no_fee, false, lp_fee, owner_fee);
+
+        SwapResult {
+            amount: amount_in_no_fee.add(&total_fee),
+            new_liquidity_in: liquidity_in.add(&amount_in_no_fee).add(&lp_fee),
+            new_liquidity_out: liquidity_out.sub(amount_out),
+            lp_fee,
+            owner_fee,
+        }
+    } else {
+        let (lp_fee, owner_fee, total_fee) = get_fee_amounts(amount_out, false, lp_fee, owner_fee);
+        let left_amount_out = amount_out.add(&total_fee);
+        let amount_in = get_amount_in_no_fee(&left_amount_out, liquidity_in, liquidity_out, params)?;
+
+        SwapResult {
+            new_liquidity_in: liquidity_in.add(&amount_in),
+            new_liquidity_out: liquidity_out.sub(&left_amount_out).add(&lp_fee),
+            amount: amount_in,
+            lp_fee,
+            owner_fee,
+        }
+    };
+    check_result(liquidity_in, liquidity_out, result, params)
+}
+
+fn check_result<T: Uint>(
+    liquidity_in: &T,
+    liquidity_out: &T,
+    result: SwapResult<T>,
+    params: &CurveParams,
+) -> Result<SwapResult<T>, MathError> {
+    if !check_swap_invariant(liquidity_in, liquidity_out, &result.new_liquidity_in, &result.new_liquidity_out, params) {
+        return Err(MathError::InvariantViolated);
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,19 @@
+// fuzzing fixtures, shared by the contract and the core crate tests
+
+pub const ITERATIONS: usize = 2_000;
+
+// xorshift64, so that failures are reproducible
+pub struct Rng(pub u64);
+
+impl Rng {
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn range(&mut self, min: u64, max: u64) -> u64 {
+        min + self.next_u64() % (max - min + 1)
+    }
+}
@@ -0,0 +1,104 @@
+/// Unsigned big integer arithmetic needed by the pricing math.
+///
+/// Subtraction is expected to panic on underflow and division to panic on a zero divisor,
+/// as both the VM and `num-bigint` do.
+pub trait Uint: Clone + PartialEq + PartialOrd {
+    fn zero() -> Self;
+    fn from_u64(value: u64) -> Self;
+    fn to_u64(&self) -> Option<u64>;
+    fn add(&self, other: &Self) -> Self;
+    fn sub(&self, other: &Self) -> Self;
+    fn mul(&self, other: &Self) -> Self;
+    fn div(&self, other: &Self) -> Self;
+    fn pow(&self, exp: u32) -> Self;
+
+    fn is_zero(&self) -> bool {
+        self == &Self::zero()
+    }
+
+    fn add_u64(&self, other: u64) -> Self {
+        self.add(&Self::from_u64(other))
+    }
+
+    fn sub_u64(&self, other: u64) -> Self {
+        self.sub(&Self::from_u64(other))
+    }
+
+    fn mul_u64(&self, other: u64) -> Self {
+        self.mul(&Self::from_u64(other))
+    }
+
+    fn div_u64(&self, other: u64) -> Self {
+        self.div(&Self::from_u64(other))
+    }
+}
+
+#[cfg(feature = "multiversx")]
+impl<M: multiversx_sc::api::ManagedTypeApi> Uint for multiversx_sc::types::BigUint<M> {
+    fn zero() -> Self {
+        multiversx_sc::types::BigUint::zero()
+    }
+
+    fn from_u64(value: u64) -> Self {
+        multiversx_sc::types::BigUint::from(value)
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        multiversx_sc::types::BigUint::to_u64(self)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn div(&self, other: &Self) -> Self {
+        self / other
+    }
+
+    fn pow(&self, exp: u32) -> Self {
+        multiversx_sc::types::BigUint::pow(self, exp)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl Uint for num_bigint::BigUint {
+    fn zero() -> Self {
+        num_bigint::BigUint::from(0u64)
+    }
+
+    fn from_u64(value: u64) -> Self {
+        num_bigint::BigUint::from(value)
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        core::convert::TryFrom::try_from(self).ok()
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+
+    fn div(&self, other: &Self) -> Self {
+        self / other
+    }
+
+    fn pow(&self, exp: u32) -> Self {
+        num_bigint::BigUint::pow(self, exp)
+    }
+}
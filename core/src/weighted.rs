@@ -0,0 +1,119 @@
+use crate::{consts::*, MathError, Uint};
+
+// out = liquidity_out * (1 - (liquidity_in / (liquidity_in + amount_in)) ^ (weight_in / weight_out))
+pub fn get_weighted_amount_out<T: Uint>(
+    amount_in: &T,
+    liquidity_in: &T,
+    liquidity_out: &T,
+    weight_in: u64,
+    weight_out: u64,
+) -> Result<T, MathError> {
+    let wad = T::from_u64(WAD);
+    let ratio = liquidity_in.add(amount_in).mul(&wad).div(liquidity_in);
+    let power = pow_wad_down(&ratio, weight_in, weight_out)?;
+    if power <= wad {
+        return Ok(T::zero());
+    }
+
+    Ok(liquidity_out.mul(&power.sub(&wad)).div(&power))
+}
+
+// in = liquidity_in * ((liquidity_out / (liquidity_out - amount_out)) ^ (weight_out / weight_in) - 1)
+pub fn get_weighted_amount_in<T: Uint>(
+    amount_out: &T,
+    liquidity_in: &T,
+    liquidity_out: &T,
+    weight_in: u64,
+    weight_out: u64,
+) -> Result<T, MathError> {
+    let wad = T::from_u64(WAD);
+    let left_liquidity_out = liquidity_out.sub(amount_out);
+    let ratio = liquidity_out.mul(&wad).add(&left_liquidity_out).sub_u64(1).div(&left_liquidity_out);
+    let power = pow_wad_up(&ratio, weight_out, weight_in)?;
+
+    Ok(liquidity_in.mul(&power.sub(&wad)).div(&wad).add_u64(1))
+}
+
+// fixed point helpers, all values are scaled by WAD and bases are >= 1
+pub fn pow_wad_down<T: Uint>(base: &T, exp_num: u64, exp_den: u64) -> Result<T, MathError> {
+    let raw = pow_wad(base, exp_num, exp_den)?;
+    let max_error = raw.mul_u64(MAX_POW_RELATIVE_ERROR).div_u64(WAD).add_u64(1);
+    if raw <= max_error {
+        return Ok(T::zero());
+    }
+
+    Ok(raw.sub(&max_error))
+}
+
+pub fn pow_wad_up<T: Uint>(base: &T, exp_num: u64, exp_den: u64) -> Result<T, MathError> {
+    let raw = pow_wad(base, exp_num, exp_den)?;
+    let max_error = raw.mul_u64(MAX_POW_RELATIVE_ERROR).div_u64(WAD).add_u64(1);
+
+    Ok(raw.add(&max_error))
+}
+
+pub fn pow_wad<T: Uint>(base: &T, exp_num: u64, exp_den: u64) -> Result<T, MathError> {
+    let wad = T::from_u64(WAD);
+    if base == &wad {
+        return Ok(wad);
+    }
+    if exp_num == exp_den {
+        return Ok(base.clone());
+    }
+
+    let exponent = ln_wad(base).mul_u64(exp_num).div_u64(exp_den);
+
+    exp_wad(&exponent)
+}
+
+// ln(x) = k * ln(2) + 2 * atanh((y - 1) / (y + 1)), where x = y * 2^k and 1 <= y < 2
+pub fn ln_wad<T: Uint>(x: &T) -> T {
+    let wad = T::from_u64(WAD);
+    let two_wad = T::from_u64(2 * WAD);
+    let mut y = x.clone();
+    let mut k = 0u64;
+    while y >= two_wad {
+        y = y.div_u64(2);
+        k += 1;
+    }
+
+    let z = y.sub(&wad).mul(&wad).div(&y.add(&wad));
+    let z_squared = z.mul(&z).div(&wad);
+    let mut term = z.clone();
+    let mut sum = z;
+    let mut divisor = 3u64;
+    loop {
+        term = term.mul(&z_squared).div(&wad);
+        if term.is_zero() {
+            break;
+        }
+
+        sum = sum.add(&term.div_u64(divisor));
+        divisor += 2;
+    }
+
+    sum.mul_u64(2).add(&T::from_u64(LN2_WAD).mul_u64(k))
+}
+
+// e^x = 2^k * e^r, where x = k * ln(2) + r and 0 <= r < ln(2)
+pub fn exp_wad<T: Uint>(x: &T) -> Result<T, MathError> {
+    let wad = T::from_u64(WAD);
+    let ln2 = T::from_u64(LN2_WAD);
+    let k = x.div(&ln2).to_u64().ok_or(MathError::PowOverflow)?;
+    let r = x.sub(&ln2.mul_u64(k));
+
+    let mut term = wad.clone();
+    let mut sum = wad.clone();
+    let mut i = 1u64;
+    loop {
+        term = term.mul(&r).div(&wad.mul_u64(i));
+        if term.is_zero() {
+            break;
+        }
+
+        sum = sum.add(&term);
+        i += 1;
+    }
+
+    Ok(sum.mul(&T::from_u64(2).pow(k as u32)))
+}
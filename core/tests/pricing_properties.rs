@@ -0,0 +1,334 @@
+use num_bigint::BigUint;
+use tfn_test_dex_core::{
+    consts::{MAX_PERCENT, MAX_POW_RELATIVE_ERROR, WAD},
+    curve::{get_amount_in_no_fee, get_amount_out_no_fee},
+    fees::get_fee_amounts,
+    stable_swap::{compute_d, compute_y, get_stable_amount_in},
+    swap::*,
+    testing::{Rng, ITERATIONS},
+    Curve, CurveParams, MathError,
+};
+
+const MAX_FEE: u64 = 1_000;
+
+fn big(value: u64) -> BigUint {
+    BigUint::from(value)
+}
+
+fn params(curve: Curve) -> CurveParams {
+    match curve {
+        Curve::ConstantProduct => CurveParams { curve, amp: 0, weight_in: 5_000, weight_out: 5_000, rate_in: 1, rate_out: 1 },
+        Curve::StableSwap => CurveParams { curve, amp: 100, weight_in: 5_000, weight_out: 5_000, rate_in: 1, rate_out: 1 },
+        Curve::Weighted => CurveParams { curve, amp: 0, weight_in: 8_000, weight_out: 2_000, rate_in: 1, rate_out: 1 },
+    }
+}
+
+struct Case {
+    liquidity_in: BigUint,
+    liquidity_out: BigUint,
+    lp_fee: u64,
+    owner_fee: u64,
+}
+
+impl Case {
+    // reserves within a few orders of magnitude of each other, as pools are in practice
+    fn random(rng: &mut Rng) -> Self {
+        let liquidity_in = big(rng.range(1_000_000, 1_000_000_000_000_000_000));
+        let liquidity_out = &liquidity_in * big(rng.range(1, 1_000)) / big(rng.range(1, 1_000));
+
+        Case {
+            liquidity_in,
+            liquidity_out: liquidity_out.max(big(1_000)),
+            lp_fee: rng.range(0, MAX_FEE),
+            owner_fee: rng.range(0, MAX_FEE),
+        }
+    }
+
+    fn total_fee(&self) -> u64 {
+        self.lp_fee + self.owner_fee
+    }
+
+    fn amount_in(&self, rng: &mut Rng) -> BigUint {
+        &self.liquidity_in * big(rng.range(1, 1_000_000)) / big(1_000_000) + big(1)
+    }
+
+    // leaves room for the fees taken from the output
+    fn amount_out(&self, rng: &mut Rng) -> BigUint {
+        &self.liquidity_out * big(rng.range(1, 500_000)) / big(1_000_000) + big(1)
+    }
+
+    fn swap_in(&self, amount_in: &BigUint, fee_in: bool, params: &CurveParams) -> SwapResult<BigUint> {
+        do_swap_fixed_input(amount_in, &self.liquidity_in, &self.liquidity_out, fee_in, self.lp_fee, self.owner_fee, params)
+            .unwrap()
+    }
+
+    fn swap_out(&self, amount_out: &BigUint, fee_in: bool, params: &CurveParams) -> SwapResult<BigUint> {
+        do_swap_fixed_output(amount_out, &self.liquidity_in, &self.liquidity_out, fee_in, self.lp_fee, self.owner_fee, params)
+            .unwrap()
+    }
+}
+
+fn for_each_swap(seed: u64, mut check: impl FnMut(&mut Rng, &Case, bool, &CurveParams)) {
+    let mut rng = Rng(seed);
+    for curve in [Curve::ConstantProduct, Curve::StableSwap, Curve::Weighted] {
+        let params = params(curve);
+        for _ in 0..ITERATIONS {
+            let case = Case::random(&mut rng);
+            for fee_in in [true, false] {
+                check(&mut rng, &case, fee_in, &params);
+            }
+        }
+    }
+}
+
+#[test]
+fn fees_never_exceed_configured_percentages() {
+    let mut rng = Rng(0x5eed_1001);
+    for _ in 0..ITERATIONS {
+        let amount = big(rng.next_u64());
+        let lp_fee = rng.range(0, MAX_FEE);
+        let owner_fee = rng.range(0, MAX_FEE);
+        let total_fee = lp_fee + owner_fee;
+
+        // fees taken from the amount
+        let (lp, owner, total) = get_fee_amounts(&amount, true, lp_fee, owner_fee);
+        assert_eq!(&lp + &owner, total);
+        assert!(&total * MAX_PERCENT <= &amount * total_fee);
+        assert!(&owner * MAX_PERCENT <= &amount * owner_fee);
+
+        // fees added on top of the amount
+        let (lp, owner, total) = get_fee_amounts(&amount, false, lp_fee, owner_fee);
+        let gross = &amount + &total;
+        assert_eq!(&lp + &owner, total);
+        assert!(&total * MAX_PERCENT <= &gross * total_fee);
+        assert!(&lp * MAX_PERCENT <= &gross * lp_fee);
+    }
+}
+
+#[test]
+fn zero_fees_are_allowed() {
+    for is_input in [true, false] {
+        let (lp, owner, total) = get_fee_amounts(&big(1_000), is_input, 0, 0);
+        assert_eq!((lp, owner, total), (big(0), big(0), big(0)));
+    }
+}
+
+// the pool's reserves product after receiving amount_in and paying amount_out
+fn product_after(x: &BigUint, y: &BigUint, amount_in: &BigUint, amount_out: &BigUint) -> BigUint {
+    (x + amount_in) * (y - amount_out)
+}
+
+// the largest output for which the reserves product does not decrease, searched instead of solved
+fn reference_amount_out(x: &BigUint, y: &BigUint, amount_in: &BigUint) -> BigUint {
+    let (mut low, mut high) = (big(0), y - 1u64);
+    while low < high {
+        let middle = (&low + &high + 1u64) / 2u64;
+        if product_after(x, y, amount_in, &middle) >= x * y {
+            low = middle;
+        } else {
+            high = middle - 1u64;
+        }
+    }
+
+    low
+}
+
+// the smallest input for which the reserves product strictly increases, searched instead of solved
+fn reference_amount_in(x: &BigUint, y: &BigUint, amount_out: &BigUint) -> BigUint {
+    let (mut low, mut high) = (big(0), x * y + 1u64);
+    while low < high {
+        let middle = (&low + &high) / 2u64;
+        if product_after(x, y, &middle, amount_out) > x * y {
+            high = middle;
+        } else {
+            low = middle + 1u64;
+        }
+    }
+
+    low
+}
+
+// the configured percentage of the amount, in basis points, rounded down
+fn reference_fee(amount: &BigUint, fee: u64) -> BigUint {
+    amount * fee / MAX_PERCENT
+}
+
+// the constant product math against a search on the reserves product
+#[test]
+fn constant_product_matches_reference() {
+    let params = params(Curve::ConstantProduct);
+    let mut rng = Rng(0x5eed_1002);
+    for _ in 0..ITERATIONS {
+        let case = Case::random(&mut rng);
+        let (x, y) = (&case.liquidity_in, &case.liquidity_out);
+        let total_fee = case.total_fee();
+
+        // fee taken from the input before it reaches the pool
+        let amount_in = case.amount_in(&mut rng);
+        let expected = reference_amount_out(x, y, &(&amount_in - reference_fee(&amount_in, total_fee)));
+        assert_eq!(case.swap_in(&amount_in, true, &params).amount, expected);
+
+        // fee taken from the output the pool pays
+        let gross_out = reference_amount_out(x, y, &amount_in);
+        let expected = &gross_out - reference_fee(&gross_out, total_fee);
+        assert_eq!(case.swap_in(&amount_in, false, &params).amount, expected);
+
+        // fee added on top of the input the pool needs, being the fee percentage of the total paid
+        let amount_out = case.amount_out(&mut rng);
+        let net_in = reference_amount_in(x, y, &amount_out);
+        let paid = case.swap_out(&amount_out, true, &params).amount;
+        assert!(paid >= net_in);
+        assert_eq!(&paid - &net_in, reference_fee(&paid, total_fee));
+    }
+}
+
+#[test]
+fn amount_in_no_fee_rounds_up() {
+    let mut rng = Rng(0x5eed_1003);
+    for curve in [Curve::ConstantProduct, Curve::StableSwap, Curve::Weighted] {
+        let params = params(curve);
+        for _ in 0..ITERATIONS {
+            let case = Case::random(&mut rng);
+            let amount_out = case.amount_out(&mut rng);
+            let amount_in =
+                get_amount_in_no_fee(&amount_out, &case.liquidity_in, &case.liquidity_out, &params).unwrap();
+            if curve == Curve::ConstantProduct {
+                let exact_numerator = &case.liquidity_in * &amount_out;
+                let exact_denominator = &case.liquidity_out - &amount_out;
+                assert!(&amount_in * &exact_denominator > exact_numerator);
+                assert!((&amount_in - 1u64) * &exact_denominator <= exact_numerator);
+            }
+
+            let received =
+                get_amount_out_no_fee(&amount_in, &case.liquidity_in, &case.liquidity_out, &params).unwrap();
+            assert!(received >= amount_out, "{:?}: paying {} gives {} < {}", curve, amount_in, received, amount_out);
+        }
+    }
+}
+
+// a 6 decimals token pegged to an 18 decimals base token trades at par, as if both had 18 decimals
+#[test]
+fn stable_swap_normalizes_decimals() {
+    let pegged = CurveParams { rate_in: 1_000_000_000_000, ..params(Curve::StableSwap) };
+    let same_decimals = params(Curve::StableSwap);
+    let mut rng = Rng(0x5eed_2601);
+    for _ in 0..ITERATIONS {
+        let whole_reserve = rng.range(1_000, 1_000_000_000);
+        let whole_amount = rng.range(1, whole_reserve / 2);
+        let (token_unit, base_unit) = (big(1_000_000), big(WAD));
+
+        let amount_out = get_amount_out_no_fee(
+            &(big(whole_amount) * &token_unit),
+            &(big(whole_reserve) * &token_unit),
+            &(big(whole_reserve) * &base_unit),
+            &pegged,
+        )
+        .unwrap();
+        let expected = get_amount_out_no_fee(
+            &(big(whole_amount) * &base_unit),
+            &(big(whole_reserve) * &base_unit),
+            &(big(whole_reserve) * &base_unit),
+            &same_decimals,
+        )
+        .unwrap();
+        // the 6 decimals input can only be as precise as its last unit
+        assert!(amount_out <= expected && &expected - &amount_out <= &base_unit / &token_unit + 2u64);
+
+        let amount_in = get_amount_in_no_fee(
+            &amount_out,
+            &(big(whole_reserve) * &token_unit),
+            &(big(whole_reserve) * &base_unit),
+            &pegged,
+        )
+        .unwrap();
+        let received = get_amount_out_no_fee(
+            &amount_in,
+            &(big(whole_reserve) * &token_unit),
+            &(big(whole_reserve) * &base_unit),
+            &pegged,
+        )
+        .unwrap();
+        assert!(received >= amount_out);
+    }
+}
+
+#[test]
+fn buying_the_whole_reserve_fails() {
+    for curve in [Curve::ConstantProduct, Curve::StableSwap, Curve::Weighted] {
+        let params = params(curve);
+        for amount_out in [big(999), big(1_000), big(1_001)] {
+            let result = get_amount_in_no_fee(&amount_out, &big(1_000), &big(1_000), &params);
+            if curve == Curve::StableSwap || amount_out >= big(1_000) {
+                assert_eq!(result, Err(MathError::NotEnoughLiquidity));
+            }
+        }
+    }
+}
+
+// a stable swap input is solved for 2 units less left in the output reserve, the margin the round trip relies on
+#[test]
+fn stable_amount_in_keeps_a_margin() {
+    let params = params(Curve::StableSwap);
+    let d = compute_d(&big(1_000_000), &big(1_000_000), params.amp);
+    let expected = compute_y(&big(900_000 - 2), &d, params.amp) - big(1_000_000) + big(1);
+    assert_eq!(get_stable_amount_in(&big(100_000), &big(1_000_000), &big(1_000_000), &params), Ok(expected));
+
+    assert!(get_stable_amount_in(&big(997), &big(1_000), &big(1_000), &params).is_ok());
+    assert_eq!(get_stable_amount_in(&big(998), &big(1_000), &big(1_000), &params), Err(MathError::NotEnoughLiquidity));
+}
+
+// paying the quoted input always buys at least the wanted output, and never much more than the input it came from
+#[test]
+fn amount_in_and_amount_out_round_trip() {
+    for_each_swap(0x5eed_1005, |rng, case, fee_in, params| {
+        let amount_out = case.amount_out(rng);
+        let amount_in = case.swap_out(&amount_out, fee_in, params).amount;
+        let received = case.swap_in(&amount_in, fee_in, params).amount;
+        assert!(received >= amount_out, "{:?} fee_in={}: {} < {}", params.curve, fee_in, received, amount_out);
+
+        let amount_in = case.amount_in(rng);
+        let result = case.swap_in(&amount_in, fee_in, params);
+        let amount_out = result.amount.clone();
+        if amount_out <= big(2) {
+            return;
+        }
+        // the constant product input is rounded up by adding 1, even when the division is exact, and the fee added to
+        // the output can exceed the needed one by 1 unit, costing the price of an output unit after the swap,
+        // the stable swap math keeps margins of a few units of the output reserve and solves D and y up to 1,
+        // the weighted math rounds each power by its relative error bound, applied to the reserve
+        let (wanted, tolerance) = match params.curve {
+            Curve::ConstantProduct => (amount_out, &result.new_liquidity_in / &result.new_liquidity_out + 2u64),
+            Curve::StableSwap => (&amount_out - 2u64, big(2)),
+            Curve::Weighted => (amount_out, &case.liquidity_in * (4 * MAX_POW_RELATIVE_ERROR) / WAD + 1u64),
+        };
+        let required = case.swap_out(&wanted, fee_in, params).amount;
+        assert!(required <= &amount_in + &tolerance, "{:?} fee_in={}: {} > {}", params.curve, fee_in, required, amount_in);
+    });
+}
+
+#[test]
+fn rounding_favours_the_pool() {
+    for_each_swap(0x5eed_1006, |rng, case, fee_in, params| {
+        let (x, y) = (&case.liquidity_in, &case.liquidity_out);
+        let amount_in = case.amount_in(rng);
+        let result = case.swap_in(&amount_in, fee_in, params);
+        // everything paid in either stays in the pool or goes to the owner, nothing more goes out
+        let paid_out = if fee_in { result.amount.clone() } else { &result.amount + &result.owner_fee };
+        let kept_in = if fee_in { &amount_in - &result.owner_fee } else { amount_in.clone() };
+        assert_eq!(&result.new_liquidity_in, &(x + &kept_in));
+        assert_eq!(&result.new_liquidity_out, &(y - &paid_out));
+        if params.curve == Curve::ConstantProduct {
+            assert!(&result.new_liquidity_in * &result.new_liquidity_out >= x * y);
+        }
+
+        let amount_out = case.amount_out(rng);
+        let result = case.swap_out(&amount_out, fee_in, params);
+        let paid_out = if fee_in { amount_out.clone() } else { &amount_out + &result.owner_fee };
+        let kept_in = if fee_in { &result.amount - &result.owner_fee } else { result.amount.clone() };
+        assert_eq!(&result.new_liquidity_in, &(x + &kept_in));
+        assert_eq!(&result.new_liquidity_out, &(y - &paid_out));
+        if params.curve == Curve::ConstantProduct {
+            assert!(&result.new_liquidity_in * &result.new_liquidity_out >= x * y);
+        }
+    });
+}
@@ -0,0 +1,15 @@
+//! Off-chain replay of Test DEX operations.
+//!
+//! Pairs are kept in memory and updated with the same math the contract uses, so a sequence of
+//! swaps and liquidity operations ends with exactly the reserves and LP supply it would on chain.
+
+pub mod pair;
+
+pub use num_bigint::BigUint;
+pub use pair::{Operation, Outcome, Pair, Side, SimError};
+pub use tfn_test_dex_core::{Curve, MathError};
+
+// applies the operations in order; like failed transactions, failed operations leave the pair unchanged
+pub fn replay(pair: &mut Pair, operations: &[Operation]) -> Vec<Result<Outcome, SimError>> {
+    operations.iter().map(|operation| pair.apply(operation)).collect()
+}
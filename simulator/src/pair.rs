@@ -0,0 +1,274 @@
+use num_bigint::BigUint;
+use tfn_test_dex_core::{
+    consts::MAX_PERCENT,
+    liquidity::*,
+    stable_swap::get_precision_rate,
+    swap::{do_swap_fixed_input, do_swap_fixed_output},
+    Curve, CurveParams, MathError, Uint,
+};
+
+// the reserves and curve of a pair, as stored by the contract
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pair {
+    pub lp_supply: BigUint,
+    pub liquidity_token: BigUint,
+    pub liquidity_base: BigUint,
+    pub lp_fee: u64,
+    pub owner_fee: u64,
+    pub curve: Curve,
+    pub amp: u64,
+    pub token_weight: u64,
+    pub base_weight: u64,
+    pub token_decimals: u32,
+    pub base_decimals: u32,
+}
+
+// the token paid in by a swap
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    Token,
+    Base,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Operation {
+    AddLiquidity { token_amount: BigUint, base_amount: BigUint },
+    RemoveLiquidity { lp_amount: BigUint },
+    SwapFixedInput { token_in: Side, amount_in: BigUint, min_amount_out: BigUint },
+    SwapFixedOutput { token_in: Side, amount_out: BigUint, max_amount_in: BigUint },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    LiquidityAdded { token_amount: BigUint, base_amount: BigUint, lp_amount: BigUint },
+    LiquidityRemoved { token_amount: BigUint, base_amount: BigUint },
+    Swapped { amount_in: BigUint, amount_out: BigUint, owner_fee: BigUint },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SimError {
+    Math(MathError),
+    ZeroAmount,
+    NoLiquidity,
+    InsufficientOutputAmount,
+    InsufficientInputAmount,
+}
+
+impl From<MathError> for SimError {
+    fn from(error: MathError) -> Self {
+        SimError::Math(error)
+    }
+}
+
+impl Pair {
+    pub fn new(
+        curve: Curve,
+        lp_fee: u64,
+        owner_fee: u64,
+        amp: u64,
+        token_weight: u64,
+        token_decimals: u32,
+        base_decimals: u32,
+    ) -> Self {
+        Pair {
+            lp_supply: BigUint::zero(),
+            liquidity_token: BigUint::zero(),
+            liquidity_base: BigUint::zero(),
+            lp_fee,
+            owner_fee,
+            curve,
+            amp,
+            token_weight,
+            base_weight: MAX_PERCENT - token_weight,
+            token_decimals,
+            base_decimals,
+        }
+    }
+
+    pub fn curve_params(&self, base_in: bool) -> CurveParams {
+        let (weight_in, weight_out) = if base_in {
+            (self.base_weight, self.token_weight)
+        } else {
+            (self.token_weight, self.base_weight)
+        };
+        let (decimals_in, decimals_out) = if base_in {
+            (self.base_decimals, self.token_decimals)
+        } else {
+            (self.token_decimals, self.base_decimals)
+        };
+
+        CurveParams {
+            curve: self.curve,
+            amp: self.amp,
+            weight_in,
+            weight_out,
+            rate_in: get_precision_rate(decimals_in),
+            rate_out: get_precision_rate(decimals_out),
+        }
+    }
+
+    pub fn apply(&mut self, operation: &Operation) -> Result<Outcome, SimError> {
+        match operation {
+            Operation::AddLiquidity { token_amount, base_amount } => self.add_liquidity(token_amount, base_amount),
+            Operation::RemoveLiquidity { lp_amount } => self.remove_liquidity(lp_amount),
+            Operation::SwapFixedInput { token_in, amount_in, min_amount_out } => {
+                self.swap_fixed_input(*token_in, amount_in, min_amount_out)
+            }
+            Operation::SwapFixedOutput { token_in, amount_out, max_amount_in } => {
+                self.swap_fixed_output(*token_in, amount_out, max_amount_in)
+            }
+        }
+    }
+
+    fn add_liquidity(&mut self, token_amount: &BigUint, base_amount: &BigUint) -> Result<Outcome, SimError> {
+        if token_amount.is_zero() || base_amount.is_zero() {
+            return Err(SimError::ZeroAmount);
+        }
+
+        let (token_added, base_added, lp_amount) = if self.lp_supply.is_zero() {
+            let lp_amount = get_initial_lp_amount(
+                token_amount,
+                base_amount,
+                self.curve,
+                self.amp,
+                self.base_weight,
+                get_precision_rate(self.token_decimals),
+                get_precision_rate(self.base_decimals),
+            );
+
+            (token_amount.clone(), base_amount.clone(), lp_amount)
+        } else {
+            let (token_added, base_added) =
+                get_optimal_amounts(token_amount, base_amount, &self.liquidity_token, &self.liquidity_base);
+            let lp_amount = get_lp_amount(
+                &token_added,
+                &base_added,
+                &self.liquidity_token,
+                &self.liquidity_base,
+                &self.lp_supply,
+            );
+
+            (token_added, base_added, lp_amount)
+        };
+        self.liquidity_token += &token_added;
+        self.liquidity_base += &base_added;
+        self.lp_supply += &lp_amount;
+
+        Ok(Outcome::LiquidityAdded {
+            token_amount: token_added,
+            base_amount: base_added,
+            lp_amount,
+        })
+    }
+
+    fn remove_liquidity(&mut self, lp_amount: &BigUint) -> Result<Outcome, SimError> {
+        if lp_amount.is_zero() {
+            return Err(SimError::ZeroAmount);
+        }
+        if lp_amount > &self.lp_supply {
+            return Err(SimError::NoLiquidity);
+        }
+
+        let (token_amount, base_amount) =
+            get_removed_amounts(lp_amount, &self.liquidity_token, &self.liquidity_base, &self.lp_supply);
+        self.liquidity_token -= &token_amount;
+        self.liquidity_base -= &base_amount;
+        self.lp_supply -= lp_amount;
+
+        Ok(Outcome::LiquidityRemoved { token_amount, base_amount })
+    }
+
+    fn swap_fixed_input(
+        &mut self,
+        token_in: Side,
+        amount_in: &BigUint,
+        min_amount_out: &BigUint,
+    ) -> Result<Outcome, SimError> {
+        if amount_in.is_zero() {
+            return Err(SimError::ZeroAmount);
+        }
+        if self.lp_supply.is_zero() {
+            return Err(SimError::NoLiquidity);
+        }
+
+        let fee_in = token_in == Side::Base;
+        let (liquidity_in, liquidity_out) = self.oriented_liquidity(token_in);
+        let result = do_swap_fixed_input(
+            amount_in,
+            liquidity_in,
+            liquidity_out,
+            fee_in,
+            self.lp_fee,
+            self.owner_fee,
+            &self.curve_params(fee_in),
+        )?;
+        if &result.amount < min_amount_out {
+            return Err(SimError::InsufficientOutputAmount);
+        }
+
+        self.set_oriented_liquidity(token_in, result.new_liquidity_in, result.new_liquidity_out);
+
+        Ok(Outcome::Swapped {
+            amount_in: amount_in.clone(),
+            amount_out: result.amount,
+            owner_fee: result.owner_fee,
+        })
+    }
+
+    fn swap_fixed_output(
+        &mut self,
+        token_in: Side,
+        amount_out: &BigUint,
+        max_amount_in: &BigUint,
+    ) -> Result<Outcome, SimError> {
+        if amount_out.is_zero() {
+            return Err(SimError::ZeroAmount);
+        }
+        if self.lp_supply.is_zero() {
+            return Err(SimError::NoLiquidity);
+        }
+
+        let fee_in = token_in == Side::Base;
+        let (liquidity_in, liquidity_out) = self.oriented_liquidity(token_in);
+        let result = do_swap_fixed_output(
+            amount_out,
+            liquidity_in,
+            liquidity_out,
+            fee_in,
+            self.lp_fee,
+            self.owner_fee,
+            &self.curve_params(fee_in),
+        )?;
+        if result.amount.is_zero() || &result.amount > max_amount_in {
+            return Err(SimError::InsufficientInputAmount);
+        }
+
+        self.set_oriented_liquidity(token_in, result.new_liquidity_in, result.new_liquidity_out);
+
+        Ok(Outcome::Swapped {
+            amount_in: result.amount,
+            amount_out: amount_out.clone(),
+            owner_fee: result.owner_fee,
+        })
+    }
+
+    fn oriented_liquidity(&self, token_in: Side) -> (&BigUint, &BigUint) {
+        match token_in {
+            Side::Token => (&self.liquidity_token, &self.liquidity_base),
+            Side::Base => (&self.liquidity_base, &self.liquidity_token),
+        }
+    }
+
+    fn set_oriented_liquidity(&mut self, token_in: Side, liquidity_in: BigUint, liquidity_out: BigUint) {
+        match token_in {
+            Side::Token => {
+                self.liquidity_token = liquidity_in;
+                self.liquidity_base = liquidity_out;
+            }
+            Side::Base => {
+                self.liquidity_base = liquidity_in;
+                self.liquidity_token = liquidity_out;
+            }
+        }
+    }
+}
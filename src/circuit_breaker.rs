@@ -0,0 +1,124 @@
+use tfn_dex::common::errors::*;
+
+use crate::common::{self, config::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait CircuitBreakerModule:
+common::config::ConfigModule
++super::helpers::HelpersModule
+{
+    #[endpoint(setPairCircuitBreaker)]
+    fn set_pair_circuit_breaker(
+        &self,
+        id: usize,
+        max_price_change: u64,
+        window: u64,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let pair = self.pair(id).get();
+        require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(max_price_change > 0 && window > 0, ERROR_WRONG_CIRCUIT_BREAKER);
+
+        self.circuit_breaker(id).set(CircuitBreaker {
+            max_price_change,
+            window,
+        });
+        self.circuit_breaker_reference(id).clear();
+    }
+
+    #[endpoint(disablePairCircuitBreaker)]
+    fn disable_pair_circuit_breaker(&self, id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let pair = self.pair(id).get();
+        require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(!self.circuit_breaker(id).is_empty(), ERROR_CIRCUIT_BREAKER_NOT_SET);
+
+        self.circuit_breaker(id).clear();
+        self.circuit_breaker_reference(id).clear();
+    }
+
+    // resumes trading on a tripped pair, either the pair owner or the subscriber can do it
+    #[endpoint(resetPairCircuitBreaker)]
+    fn reset_pair_circuit_breaker(&self, id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+        require!(!self.circuit_breaker_trip(id).is_empty(), ERROR_CIRCUIT_BREAKER_NOT_TRIPPED);
+
+        let mut pair = self.pair(id).get();
+        let caller = self.blockchain().get_caller();
+        if pair.owner != caller {
+            self.only_subscriber(&caller);
+        }
+
+        self.circuit_breaker_trip(id).clear();
+        self.circuit_breaker_reference(id).clear();
+        // a state forced by the subscriber is kept
+        if pair.state == PairState::ActiveNoSwap && pair.lp_supply > 0 && !self.pair_state_forced(id).get() {
+            pair.state = PairState::Active;
+            self.pair(id).set(pair);
+        }
+    }
+
+    #[view(getCircuitBreakerTrip)]
+    fn get_circuit_breaker_trip(&self, id: usize) -> Option<CircuitBreakerTrip<Self::Api>> {
+        if self.circuit_breaker_trip(id).is_empty() {
+            return None;
+        }
+
+        Some(self.circuit_breaker_trip(id).get())
+    }
+
+    // compares the price after a swap to the one at the start of the current window and halts trading if it moved too much
+    fn check_circuit_breaker(&self, old_pair: &Pair<Self::Api>, pair: &mut Pair<Self::Api>) {
+        if self.circuit_breaker(pair.id).is_empty() {
+            return;
+        }
+
+        let config = self.circuit_breaker(pair.id).get();
+        let block = self.blockchain().get_block_nonce();
+        let reference_mapper = self.circuit_breaker_reference(pair.id);
+        if reference_mapper.is_empty() || block >= reference_mapper.get().block + config.window {
+            reference_mapper.set(PriceReference {
+                block,
+                price: self.get_spot_price(old_pair),
+            });
+        }
+
+        let reference = reference_mapper.get();
+        let price = self.get_spot_price(pair);
+        let price_change = self.get_price_impact(&reference.price, &price);
+        if price_change <= config.max_price_change {
+            return;
+        }
+
+        pair.state = PairState::ActiveNoSwap;
+        let trip = CircuitBreakerTrip {
+            block,
+            timestamp: self.blockchain().get_block_timestamp(),
+            reference_price: reference.price,
+            price,
+            price_change,
+        };
+        self.circuit_breaker_tripped_event(pair.id, &trip);
+        self.circuit_breaker_trip(pair.id).set(trip);
+    }
+
+    #[event("circuitBreakerTripped")]
+    fn circuit_breaker_tripped_event(&self, #[indexed] pair_id: usize, trip: &CircuitBreakerTrip<Self::Api>);
+
+    #[view(getPairCircuitBreaker)]
+    #[storage_mapper("circuit_breakers")]
+    fn circuit_breaker(&self, pair_id: usize) -> SingleValueMapper<CircuitBreaker>;
+
+    #[storage_mapper("circuit_breaker_reference")]
+    fn circuit_breaker_reference(&self, pair_id: usize) -> SingleValueMapper<PriceReference<Self::Api>>;
+
+    #[storage_mapper("circuit_breaker_trip")]
+    fn circuit_breaker_trip(&self, pair_id: usize) -> SingleValueMapper<CircuitBreakerTrip<Self::Api>>;
+}
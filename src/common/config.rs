@@ -10,6 +10,7 @@ use tfn_platform::common::config::ProxyTrait as _;
 pub enum State {
     Inactive,
     Active,
+    Emergency,
 }
 
 #[type_abi]
@@ -18,6 +19,58 @@ pub enum PairState {
     Inactive,
     ActiveNoSwap,
     Active,
+    Delisted,
+    PendingApproval,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Curve {
+    ConstantProduct,
+    StableSwap,
+    Weighted,
+}
+
+impl From<Curve> for tfn_test_dex_core::Curve {
+    fn from(curve: Curve) -> Self {
+        match curve {
+            Curve::ConstantProduct => tfn_test_dex_core::Curve::ConstantProduct,
+            Curve::StableSwap => tfn_test_dex_core::Curve::StableSwap,
+            Curve::Weighted => tfn_test_dex_core::Curve::Weighted,
+        }
+    }
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct AmpRamp {
+    pub initial_amp: u64,
+    pub future_amp: u64,
+    pub initial_time: u64,
+    pub future_time: u64,
+}
+
+// the fees, in basis points, pair owners of a student tier can set
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct FeeRange {
+    pub min_lp_fee: u64,
+    pub max_lp_fee: u64,
+    pub min_owner_fee: u64,
+    pub max_owner_fee: u64,
+}
+
+// what the token issuer can do with the balances held by the SC, as reported by the ESDT system SC
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Default, Debug)]
+pub struct TokenRisk {
+    pub inspected: bool,
+    pub can_pause: bool,
+    pub can_freeze: bool,
+    pub can_wipe: bool,
+    pub can_upgrade: bool,
+    pub can_change_owner: bool,
+    pub can_mint: bool,
 }
 
 #[type_abi]
@@ -34,6 +87,262 @@ pub struct Pair<M: ManagedTypeApi> {
     pub owner_fee: u64,
     pub liquidity_token: BigUint<M>,
     pub liquidity_base: BigUint<M>,
+    pub curve: Curve,
+    pub amp: AmpRamp,
+    pub token_weight: u64,
+    pub base_weight: u64,
+    pub token_decimals: u32,
+    pub max_price_impact: u64,
+    pub max_swap_ratio: u64,
+    pub token_risk: TokenRisk,
+    pub base_token_risk: TokenRisk,
+    pub base_decimals: u32,
+}
+
+// the pair layout stored before storage versioning, read once by the upgrade migration
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct LegacyPair<M: ManagedTypeApi> {
+    pub id: usize,
+    pub owner: ManagedAddress<M>,
+    pub state: PairState,
+    pub token: TokenIdentifier<M>,
+    pub base_token: TokenIdentifier<M>,
+    pub lp_token: TokenIdentifier<M>,
+    pub lp_supply: BigUint<M>,
+    pub lp_fee: u64,
+    pub owner_fee: u64,
+    pub liquidity_token: BigUint<M>,
+    pub liquidity_base: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct CircuitBreaker {
+    pub max_price_change: u64,
+    pub window: u64,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct PriceReference<M: ManagedTypeApi> {
+    pub block: u64,
+    pub price: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct CircuitBreakerTrip<M: ManagedTypeApi> {
+    pub block: u64,
+    pub timestamp: u64,
+    pub reference_price: BigUint<M>,
+    pub price: BigUint<M>,
+    pub price_change: u64,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SwapKind {
+    FixedInput,
+    FixedOutput,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct SwapInstruction<M: ManagedTypeApi> {
+    pub token_out: EgldOrEsdtTokenIdentifier<M>,
+    pub kind: SwapKind,
+    pub limit: BigUint<M>,
+}
+
+// fees are always paid in the base token and prices are expressed in base token units for one whole token
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct SwapQuote<M: ManagedTypeApi> {
+    pub pair_id: usize,
+    pub token_in: TokenIdentifier<M>,
+    pub token_out: TokenIdentifier<M>,
+    pub amount_in: BigUint<M>,
+    pub amount_out: BigUint<M>,
+    pub spot_price_before: BigUint<M>,
+    pub spot_price_after: BigUint<M>,
+    pub price_impact: u64,
+    pub lp_fee: BigUint<M>,
+    pub owner_fee: BigUint<M>,
+    pub new_liquidity_token: BigUint<M>,
+    pub new_liquidity_base: BigUint<M>,
+}
+
+// Buy and Sell are from the trader's point of view on the pair's token; price is base_amount / token_amount scaled by 10^18
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TradeKind {
+    Buy,
+    Sell,
+    AddLiquidity,
+    RemoveLiquidity,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct TradeRecord<M: ManagedTypeApi> {
+    pub pair_id: usize,
+    pub kind: TradeKind,
+    pub token_amount: BigUint<M>,
+    pub base_amount: BigUint<M>,
+    pub price: BigUint<M>,
+    pub timestamp: u64,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct TokenValue<M: ManagedTypeApi> {
+    pub token: TokenIdentifier<M>,
+    pub balance: BigUint<M>,
+    pub priced: bool,
+    pub value: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct PortfolioValue<M: ManagedTypeApi> {
+    pub total: BigUint<M>,
+    pub tokens: ManagedVec<M, TokenValue<M>>,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct TokenReserve<M: ManagedTypeApi> {
+    pub token: TokenIdentifier<M>,
+    pub booked: BigUint<M>,
+    pub balance: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct PriceObservation<M: ManagedTypeApi> {
+    pub timestamp: u64,
+    pub price: BigUint<M>,
+    pub price_cumulative: BigUint<M>,
+}
+
+// one per block with swaps, volume being the base token amount swapped in that block
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct SwapObservation<M: ManagedTypeApi> {
+    pub timestamp: u64,
+    pub block: u64,
+    pub liquidity_token: BigUint<M>,
+    pub liquidity_base: BigUint<M>,
+    pub price: BigUint<M>,
+    pub volume: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct Candle<M: ManagedTypeApi> {
+    pub open_time: u64,
+    pub open: BigUint<M>,
+    pub high: BigUint<M>,
+    pub low: BigUint<M>,
+    pub close: BigUint<M>,
+    pub volume: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct DynamicFee {
+    pub min_fee: u64,
+    pub max_fee: u64,
+    pub window: u64,
+    pub sensitivity: u64,
+}
+
+// rewards are shared by weight: staked LP tokens weigh their amount, locked ones are boosted; reward_per_share is scaled by 10^18
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct Farm<M: ManagedTypeApi> {
+    pub pair_id: usize,
+    pub lp_token: TokenIdentifier<M>,
+    pub reward_token: TokenIdentifier<M>,
+    pub reward_per_block: BigUint<M>,
+    pub reward_reserve: BigUint<M>,
+    pub rewards_unclaimed: BigUint<M>,
+    pub total_staked: BigUint<M>,
+    pub total_weight: BigUint<M>,
+    pub reward_per_share: BigUint<M>,
+    pub last_reward_block: u64,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct FarmPosition<M: ManagedTypeApi> {
+    pub staked: BigUint<M>,
+    pub reward_per_share: BigUint<M>,
+    pub pending_rewards: BigUint<M>,
+}
+
+// penalty_per_weight is scaled by 10^18
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct LockPool<M: ManagedTypeApi> {
+    pub total_locked: BigUint<M>,
+    pub total_weight: BigUint<M>,
+    pub penalty_per_weight: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct LockPosition<M: ManagedTypeApi> {
+    pub pair_id: usize,
+    pub amount: BigUint<M>,
+    pub weight: BigUint<M>,
+    pub lock_time: u64,
+    pub unlock_time: u64,
+    pub reward_per_share: BigUint<M>,
+    pub penalty_per_weight: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct ClPool<M: ManagedTypeApi> {
+    pub id: usize,
+    pub owner: ManagedAddress<M>,
+    pub state: PairState,
+    pub token: TokenIdentifier<M>,
+    pub base_token: TokenIdentifier<M>,
+    pub position_token: TokenIdentifier<M>,
+    pub lp_fee: u64,
+    pub owner_fee: u64,
+    pub tick_spacing: u32,
+    pub sqrt_price: BigUint<M>,
+    pub current_tick: i32,
+    pub liquidity: BigUint<M>,
+    pub fee_growth_token: BigUint<M>,
+    pub fee_growth_base: BigUint<M>,
+    pub reserve_token: BigUint<M>,
+    pub reserve_base: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct ClTick<M: ManagedTypeApi> {
+    pub liquidity_lower: BigUint<M>,
+    pub liquidity_upper: BigUint<M>,
+    pub fee_growth_outside_token: BigUint<M>,
+    pub fee_growth_outside_base: BigUint<M>,
+}
+
+#[type_abi]
+#[derive(ManagedVecItem, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Debug)]
+pub struct ClPosition<M: ManagedTypeApi> {
+    pub pool_id: usize,
+    pub nonce: u64,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: BigUint<M>,
+    pub fee_growth_inside_token: BigUint<M>,
+    pub fee_growth_inside_base: BigUint<M>,
 }
 
 #[multiversx_sc::module]
@@ -54,6 +363,13 @@ pub trait ConfigModule {
         self.state().set(State::Inactive);
     }
 
+    // only liquidity withdrawals remain possible, whatever the pair states
+    #[only_owner]
+    #[endpoint(setStateEmergency)]
+    fn set_state_emergency(&self) {
+        self.state().set(State::Emergency);
+    }
+
     #[view(getState)]
     #[storage_mapper("state")]
     fn state(&self) -> SingleValueMapper<State>;
@@ -90,6 +406,20 @@ pub trait ConfigModule {
         base_tokens
     }
 
+    // denied tokens
+    #[storage_mapper("denied_tokens")]
+    fn denied_tokens(&self) -> UnorderedSetMapper<TokenIdentifier>;
+
+    #[view(getDeniedTokens)]
+    fn get_denied_tokens(&self) -> ManagedVec<TokenIdentifier<Self::Api>> {
+        let mut denied_tokens = ManagedVec::new();
+        for token in self.denied_tokens().iter() {
+            denied_tokens.push(token);
+        }
+
+        denied_tokens
+    }
+
     // pairs
     #[view(getPair)]
     #[storage_mapper("pairs")]
@@ -99,10 +429,31 @@ pub trait ConfigModule {
     #[storage_mapper("last_pair_id")]
     fn last_pair_id(&self) -> SingleValueMapper<usize>;
 
+    #[storage_mapper("pairs")]
+    fn legacy_pair(&self, id: usize) -> SingleValueMapper<LegacyPair<Self::Api>>;
+
+    #[view(getStorageVersion)]
+    #[storage_mapper("storage_version")]
+    fn storage_version(&self) -> SingleValueMapper<u32>;
+
+    // concentrated liquidity pools share the pair ids
+    #[view(getClPool)]
+    #[storage_mapper("cl_pools")]
+    fn cl_pool(&self, id: usize) -> SingleValueMapper<ClPool<Self::Api>>;
+
+    // set when the subscriber forces a pair's state, the owner can't change it until released
+    #[view(getPairStateForced)]
+    #[storage_mapper("pair_state_forced")]
+    fn pair_state_forced(&self, id: usize) -> SingleValueMapper<bool>;
+
     #[view(getPairs)]
     fn get_pairs(&self) -> ManagedVec<Pair<Self::Api>> {
         let mut pairs = ManagedVec::new();
         for id in 0..self.last_pair_id().get() {
+            if self.pair(id).is_empty() {
+                continue;
+            }
+
             pairs.push(self.pair(id).get());
         }
 
@@ -112,6 +463,10 @@ pub trait ConfigModule {
     #[view(getPairByTickers)]
     fn get_pair_by_tickers(&self, token1: &TokenIdentifier, token2: &TokenIdentifier) -> Option<Pair<Self::Api>> {
         for id in 0..self.last_pair_id().get() {
+            if self.pair(id).is_empty() {
+                continue;
+            }
+
             let pair = self.pair(id).get();
             if &pair.base_token == token1 && &pair.token == token2 {
                 return Some(pair);
@@ -128,6 +483,10 @@ pub trait ConfigModule {
     fn get_pair_by_lp_token(&self, lp_token: &TokenIdentifier) -> Option<Pair<Self::Api>> {
         let last_pair_id = self.last_pair_id().get();
         for id in 0..last_pair_id {
+            if self.pair(id).is_empty() {
+                continue;
+            }
+
             let pair = self.pair(id).get();
             if &pair.lp_token == lp_token {
                 return Some(pair);
@@ -137,6 +496,22 @@ pub trait ConfigModule {
         None
     }
 
+    // helpers
+    fn check_whitelisted(&self, address: &ManagedAddress) {
+        self.platform_contract_proxy()
+            .contract(self.platform_sc().get())
+            .check_whitelisted(address)
+            .execute_on_dest_context::<()>();
+    }
+
+    fn only_subscriber(&self, address: &ManagedAddress) {
+        let id: Option<u64> = self.platform_contract_proxy()
+            .contract(self.platform_sc().get())
+            .get_subscriber_id_by_address(address)
+            .execute_on_dest_context();
+        require!(id.is_some(), ERROR_NOT_SUBSCRIBER);
+    }
+
     // proxies
     #[proxy]
     fn platform_contract_proxy(&self) -> tfn_platform::Proxy<Self::Api>;
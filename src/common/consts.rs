@@ -0,0 +1,29 @@
+pub use tfn_test_dex_core::consts::{
+    LN2_WAD, MAX_POW_RELATIVE_ERROR, STABLE_SWAP_COINS, STABLE_SWAP_MAX_ITERATIONS, WAD, WEIGHTED_INVARIANT_TOLERANCE,
+};
+
+pub const MIN_AMP: u64 = 1;
+pub const MAX_AMP: u64 = 1_000_000;
+pub const MAX_AMP_CHANGE: u64 = 10;
+pub const MIN_RAMP_TIME: u64 = 86_400;
+pub const MIN_TOKEN_WEIGHT: u64 = 200;
+pub const MIN_TICK: i32 = -400_000;
+pub const MAX_TICK: i32 = 400_000;
+pub const MAX_TICK_SPACING: u32 = 1_000;
+pub const LN_TICK_BASE_WAD: u64 = 99_995_000_333_308;
+pub const FEE_GROWTH_BITS: u32 = 256;
+pub const CL_POSITION_TOKEN_SUFFIX: &[u8] = b"Pos";
+pub const MAX_TOKEN_DECIMALS: u32 = 18;
+pub const NUM_DECIMALS_PROPERTY: &[u8] = b"NumDecimals-";
+pub const STORAGE_VERSION: u32 = 1;
+pub const LEGACY_TOKEN_DECIMALS: u32 = 18;
+pub const MIN_LOCK_PERIOD: u64 = 86_400;
+pub const MAX_LOCK_PERIOD: u64 = 365 * 86_400;
+pub const MAX_LOCK_BOOST: u64 = 15_000;
+pub const MAX_EARLY_UNLOCK_PENALTY: u64 = 5_000;
+pub const LOCK_TOKEN_NAME: &[u8] = b"LockedLP";
+pub const LOCK_TOKEN_TICKER: &[u8] = b"LOCKEDLP";
+pub const EGLD_TOKEN_IDENTIFIER: &[u8] = b"EGLD-000000";
+pub const MAX_TRADE_HISTORY: u64 = 100;
+pub const MAX_SWAP_OBSERVATIONS: u64 = 1_000;
+pub const MAX_OHLC_CANDLES: u64 = 500;
@@ -1 +1,64 @@
 pub static ERROR_NOT_PAIR_OWNER: &[u8] = b"only the pair owner can call this function";
+pub static ERROR_WRONG_AMP: &[u8] = b"wrong amplification coefficient";
+pub static ERROR_NOT_STABLE_SWAP: &[u8] = b"pair does not use the stable swap curve";
+pub static ERROR_RAMP_TOO_SOON: &[u8] = b"amplification was changed too recently";
+pub static ERROR_RAMP_TOO_SHORT: &[u8] = b"amplification ramp is too short";
+pub static ERROR_AMP_CHANGE_TOO_LARGE: &[u8] = b"amplification change is too large";
+pub static ERROR_WRONG_WEIGHTS: &[u8] = b"wrong token weights";
+pub static ERROR_POW_OVERFLOW: &[u8] = b"power calculation overflow";
+pub static ERROR_WRONG_TICK_SPACING: &[u8] = b"wrong tick spacing";
+pub static ERROR_WRONG_TICK_RANGE: &[u8] = b"wrong tick range";
+pub static ERROR_PRICE_OUT_OF_RANGE: &[u8] = b"price out of range";
+pub static ERROR_NOT_ENOUGH_CL_LIQUIDITY: &[u8] = b"not enough liquidity in range";
+pub static ERROR_NOT_ENOUGH_LIQUIDITY: &[u8] = b"not enough liquidity";
+pub static ERROR_POSITION_NOT_FOUND: &[u8] = b"position not found";
+pub static ERROR_WRONG_FEES: &[u8] = b"wrong fees";
+pub static ERROR_WRONG_FEE_WINDOW: &[u8] = b"wrong fee window";
+pub static ERROR_DYNAMIC_FEE_NOT_SET: &[u8] = b"dynamic fee is not set";
+pub static ERROR_INVARIANT_VIOLATED: &[u8] = b"swap invariant violated";
+pub static ERROR_NOTHING_TO_SKIM: &[u8] = b"nothing to skim";
+pub static ERROR_NOTHING_TO_SYNC: &[u8] = b"nothing to sync";
+pub static ERROR_WRONG_BATCH_SWAP: &[u8] = b"number of payments and instructions differ";
+pub static ERROR_WRONG_DECIMALS: &[u8] = b"wrong decimals";
+pub static ERROR_WRONG_SWAP_LIMITS: &[u8] = b"wrong swap limits";
+pub static ERROR_PRICE_IMPACT_TOO_HIGH: &[u8] = b"price impact too high";
+pub static ERROR_SWAP_TOO_LARGE: &[u8] = b"swap too large";
+pub static ERROR_WRONG_CIRCUIT_BREAKER: &[u8] = b"wrong circuit breaker";
+pub static ERROR_CIRCUIT_BREAKER_NOT_SET: &[u8] = b"circuit breaker not set";
+pub static ERROR_CIRCUIT_BREAKER_NOT_TRIPPED: &[u8] = b"circuit breaker not tripped";
+pub static ERROR_PAIR_DELISTED: &[u8] = b"pair delisted";
+pub static ERROR_TOKEN_DENIED: &[u8] = b"token denied";
+pub static ERROR_TOKEN_ALREADY_DENIED: &[u8] = b"token already denied";
+pub static ERROR_TOKEN_NOT_DENIED: &[u8] = b"token not denied";
+pub static ERROR_PAIR_NOT_DELISTED: &[u8] = b"pair not delisted";
+pub static ERROR_PAIR_HAS_LIQUIDITY: &[u8] = b"pair has liquidity";
+pub static ERROR_FARM_NOT_FOUND: &[u8] = b"farm not found";
+pub static ERROR_NOT_STAKED: &[u8] = b"not staked";
+pub static ERROR_NO_REWARDS: &[u8] = b"no rewards";
+pub static ERROR_CANNOT_COMPOUND: &[u8] = b"rewards cannot be compounded into this pair";
+pub static ERROR_LOCK_TOKEN_ALREADY_ISSUED: &[u8] = b"lock token already issued";
+pub static ERROR_LOCK_TOKEN_NOT_ISSUED: &[u8] = b"lock token not issued";
+pub static ERROR_LOCK_TOKEN_TRANSFER_ROLE_NOT_SET: &[u8] = b"lock token transfer role not set";
+pub static ERROR_WRONG_LOCK_PERIOD: &[u8] = b"wrong lock period";
+pub static ERROR_LOCK_NOT_FOUND: &[u8] = b"lock not found";
+pub static ERROR_WEGLD_NOT_SET: &[u8] = b"wegld wrapper not set";
+pub static ERROR_TOKEN_NOT_TRANSFERABLE: &[u8] = b"token is paused, frozen or has limited transfers";
+pub static ERROR_TOKEN_NOT_INSPECTED: &[u8] = b"token not inspected";
+pub static ERROR_TOKEN_RISKY: &[u8] = b"token can be paused or frozen by its issuer";
+pub static ERROR_REFERRER_EXISTS: &[u8] = b"referrer already registered";
+pub static ERROR_REFERRER_NOT_FOUND: &[u8] = b"referrer not found";
+pub static ERROR_WRONG_REFERRAL_FEE_SHARE: &[u8] = b"wrong referral fee share";
+pub static ERROR_NO_REFERRAL_EARNINGS: &[u8] = b"no referral earnings";
+pub static ERROR_WRONG_OHLC_PARAMS: &[u8] = b"wrong ohlc interval or count";
+pub static ERROR_NO_ORACLE_ROUTE: &[u8] = b"no liquid route between the tokens";
+pub static ERROR_ORACLE_PRICE_STALE: &[u8] = b"oracle price is stale";
+pub static ERROR_ORACLE_HISTORY_TOO_SHORT: &[u8] = b"not enough price history for the period";
+pub static ERROR_WRONG_ORACLE_PERIOD: &[u8] = b"wrong oracle period";
+pub static ERROR_TOO_MANY_PAIRS: &[u8] = b"pair limit reached";
+pub static ERROR_INITIAL_LIQUIDITY_TOO_LOW: &[u8] = b"initial liquidity too low";
+pub static ERROR_PAIR_PENDING_APPROVAL: &[u8] = b"pair pending approval";
+pub static ERROR_PAIR_NOT_PENDING_APPROVAL: &[u8] = b"pair not pending approval";
+pub static ERROR_WRONG_FEE_RANGE: &[u8] = b"wrong fee range";
+pub static ERROR_FEES_OUT_OF_RANGE: &[u8] = b"fees out of the allowed range";
+pub static ERROR_PAIR_STATE_FORCED: &[u8] = b"pair state forced by the subscriber";
+pub static ERROR_PAIR_STATE_NOT_FORCED: &[u8] = b"pair state not forced";
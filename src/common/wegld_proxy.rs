@@ -0,0 +1,16 @@
+multiversx_sc::imports!();
+
+// the endpoints of the WEGLD wrapper (egld-esdt-swap) contract used by the DEX
+#[multiversx_sc::proxy]
+pub trait WegldSwapContract {
+    #[payable("EGLD")]
+    #[endpoint(wrapEgld)]
+    fn wrap_egld(&self) -> EsdtTokenPayment<Self::Api>;
+
+    #[payable("*")]
+    #[endpoint(unwrapEgld)]
+    fn unwrap_egld(&self);
+
+    #[view(getWrappedEgldTokenId)]
+    fn wrapped_egld_token_id(&self) -> TokenIdentifier;
+}
@@ -0,0 +1,988 @@
+use tfn_dex::common::{consts::*, errors::*};
+
+use crate::common::{self, config::*, consts::*, errors::*};
+use tfn_test_dex_core::weighted::{exp_wad, ln_wad};
+
+multiversx_sc::imports!();
+
+pub struct ClSwapResult<M: ManagedTypeApi> {
+    pub amount_in: BigUint<M>,
+    pub amount_out: BigUint<M>,
+    pub lp_fee: BigUint<M>,
+    pub owner_fee: BigUint<M>,
+}
+
+pub struct ClSwapStep<M: ManagedTypeApi> {
+    pub sqrt_price: BigUint<M>,
+    pub amount_in: BigUint<M>,
+    pub amount_out: BigUint<M>,
+    pub fee: BigUint<M>,
+}
+
+#[multiversx_sc::module]
+pub trait ConcentratedModule:
+common::config::ConfigModule
++super::helpers::HelpersModule
++super::reserves::ReservesModule
++super::token_risk::TokenRiskModule
++super::pair_policy::PairPolicyModule
+{
+    #[payable("EGLD")]
+    #[endpoint(createClPool)]
+    fn create_cl_pool(
+        &self,
+        base_token: TokenIdentifier,
+        token: TokenIdentifier,
+        lp_fee: u64,
+        owner_fee: u64,
+        tick_spacing: u32,
+        initial_price: BigUint,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+
+        let caller = self.blockchain().get_caller();
+        self.check_whitelisted(&caller);
+        self.check_pair_creation_policy(&caller, lp_fee, owner_fee);
+        require!(self.base_tokens().contains(&base_token), ERROR_WRONG_BASE_TOKEN);
+        require!(base_token != token, ERROR_WRONG_BASE_TOKEN);
+        require!(!self.denied_tokens().contains(&token), ERROR_TOKEN_DENIED);
+        self.check_token_transferable(&token);
+        self.check_token_risk(&token, &base_token);
+        require!(self.get_pair_by_tickers(&token, &base_token).is_none(), ERROR_PAIR_EXISTS);
+        require!(self.get_cl_pool_by_tickers(&token, &base_token).is_none(), ERROR_PAIR_EXISTS);
+        require!(lp_fee + owner_fee < MAX_PERCENT, ERROR_WRONG_FEES);
+        require!(tick_spacing > 0 && tick_spacing <= MAX_TICK_SPACING, ERROR_WRONG_TICK_SPACING);
+        require!(initial_price > 0, ERROR_ZERO_AMOUNT);
+
+        let sqrt_price = (initial_price * WAD).sqrt();
+        require!(
+            sqrt_price >= self.get_sqrt_price_at_tick(MIN_TICK) && sqrt_price < self.get_sqrt_price_at_tick(MAX_TICK),
+            ERROR_PRICE_OUT_OF_RANGE
+        );
+        let tick = self.get_tick_at_sqrt_price(&sqrt_price);
+
+        let mut ticker = token.ticker().concat(base_token.ticker());
+        if ticker.len() > 20 - CL_POSITION_TOKEN_SUFFIX.len() {
+            ticker = ticker.copy_slice(0, 20 - CL_POSITION_TOKEN_SUFFIX.len()).unwrap();
+        }
+        let name = ticker.clone().concat(ManagedBuffer::from(CL_POSITION_TOKEN_SUFFIX));
+        if ticker.len() > 10 {
+            ticker = ticker.copy_slice(0, 10).unwrap();
+        }
+        let issue_cost = self.call_value().egld_value().clone_value();
+
+        self.send()
+            .esdt_system_sc_proxy()
+            .issue_and_set_all_roles(
+                issue_cost,
+                name,
+                ticker,
+                EsdtTokenType::NonFungible,
+                0,
+            )
+            .with_callback(self.callbacks().cl_position_token_issue_callback(
+                caller,
+                &base_token,
+                &token,
+                lp_fee,
+                owner_fee,
+                tick_spacing,
+                sqrt_price,
+                tick,
+            ))
+            .async_call_and_exit();
+    }
+
+    #[callback]
+    fn cl_position_token_issue_callback(
+        &self,
+        caller: ManagedAddress,
+        base_token: &TokenIdentifier,
+        token: &TokenIdentifier,
+        lp_fee: u64,
+        owner_fee: u64,
+        tick_spacing: u32,
+        sqrt_price: BigUint,
+        tick: i32,
+        #[call_result] result: ManagedAsyncCallResult<TokenIdentifier>,
+    ) {
+        match result {
+            ManagedAsyncCallResult::Ok(position_token) => {
+                let id = self.last_pair_id().get();
+                let state = if self.pair_approval_required().get() {
+                    PairState::PendingApproval
+                } else {
+                    PairState::ActiveNoSwap
+                };
+                let pool = ClPool {
+                    id,
+                    owner: caller,
+                    state,
+                    token: token.clone(),
+                    base_token: base_token.clone(),
+                    position_token,
+                    lp_fee,
+                    owner_fee,
+                    tick_spacing,
+                    sqrt_price,
+                    current_tick: tick,
+                    liquidity: BigUint::zero(),
+                    fee_growth_token: BigUint::zero(),
+                    fee_growth_base: BigUint::zero(),
+                    reserve_token: BigUint::zero(),
+                    reserve_base: BigUint::zero(),
+                };
+                self.last_pair_id().set(id + 1);
+                self.cl_pool(id).set(pool);
+            }
+            ManagedAsyncCallResult::Err(_) => {
+                let issue_cost = self.call_value().egld_value();
+                self.send().direct_egld(&caller, &issue_cost);
+            }
+        }
+    }
+
+    #[endpoint(setClPoolState)]
+    fn set_cl_pool_state(&self, id: usize, state: PairState) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.cl_pool(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let mut pool = self.cl_pool(id).get();
+        require!(pool.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(!self.pair_state_forced(id).get(), ERROR_PAIR_STATE_FORCED);
+        require!(pool.state != PairState::Delisted && state != PairState::Delisted, ERROR_PAIR_DELISTED);
+        require!(
+            pool.state != PairState::PendingApproval && state != PairState::PendingApproval,
+            ERROR_PAIR_PENDING_APPROVAL
+        );
+        if state == PairState::Active {
+            require!(!self.cl_pool_ticks(id).is_empty(), ERROR_NO_LIQUIDITY);
+        }
+
+        pool.state = state;
+        self.cl_pool(id).set(pool);
+    }
+
+    #[endpoint(changeClPoolFees)]
+    fn change_cl_pool_fees(
+        &self,
+        id: usize,
+        new_lp_fee: u64,
+        new_owner_fee: u64,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.cl_pool(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let mut pool = self.cl_pool(id).get();
+        require!(pool.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        // the swap math divides by MAX_PERCENT - total fee
+        require!(new_lp_fee + new_owner_fee < MAX_PERCENT, ERROR_WRONG_FEES);
+        self.check_fee_range(&pool.owner, new_lp_fee, new_owner_fee);
+
+        pool.lp_fee = new_lp_fee;
+        pool.owner_fee = new_owner_fee;
+        self.cl_pool(id).set(pool);
+    }
+
+    #[payable("*")]
+    #[endpoint(addClPosition)]
+    fn add_cl_position(
+        &self,
+        pool_id: usize,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.cl_pool(pool_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let mut pool = self.cl_pool(pool_id).get();
+        require!(pool.state != PairState::Inactive, ERROR_PAIR_NOT_ACTIVE);
+        require!(pool.state != PairState::Delisted, ERROR_PAIR_DELISTED);
+        require!(pool.state != PairState::PendingApproval, ERROR_PAIR_PENDING_APPROVAL);
+        require!(tick_lower < tick_upper && tick_lower >= MIN_TICK && tick_upper <= MAX_TICK, ERROR_WRONG_TICK_RANGE);
+        let spacing = pool.tick_spacing as i32;
+        require!(tick_lower % spacing == 0 && tick_upper % spacing == 0, ERROR_WRONG_TICK_RANGE);
+
+        let payments = self.call_value().all_esdt_transfers();
+        require!(!payments.is_empty() && payments.len() <= 2, ERROR_WRONG_PAYMENT);
+
+        let mut token_amount = BigUint::zero();
+        let mut base_amount = BigUint::zero();
+        for payment in payments.iter() {
+            if payment.token_identifier == pool.token {
+                token_amount += &payment.amount;
+            } else if payment.token_identifier == pool.base_token {
+                base_amount += &payment.amount;
+            } else {
+                sc_panic!(ERROR_WRONG_PAYMENT);
+            }
+        }
+
+        let sqrt_price_lower = self.get_sqrt_price_at_tick(tick_lower);
+        let sqrt_price_upper = self.get_sqrt_price_at_tick(tick_upper);
+        let liquidity = self.get_cl_liquidity_for_amounts(
+            &pool.sqrt_price,
+            &sqrt_price_lower,
+            &sqrt_price_upper,
+            &token_amount,
+            &base_amount,
+        );
+        require!(liquidity > 0, ERROR_ZERO_AMOUNT);
+
+        let (token_added, base_added) = self.get_cl_amounts_for_liquidity(
+            &pool.sqrt_price,
+            &sqrt_price_lower,
+            &sqrt_price_upper,
+            &liquidity,
+            true,
+        );
+        if self.cl_pool_ticks(pool_id).is_empty() {
+            require!(base_added >= self.min_initial_liquidity(&pool.base_token).get(), ERROR_INITIAL_LIQUIDITY_TOO_LOW);
+        }
+        self.update_cl_tick(&pool, tick_lower, &liquidity, true, true);
+        self.update_cl_tick(&pool, tick_upper, &liquidity, false, true);
+        if pool.current_tick >= tick_lower && pool.current_tick < tick_upper {
+            pool.liquidity += &liquidity;
+        }
+        let (fee_growth_inside_token, fee_growth_inside_base) =
+            self.get_fee_growth_inside(&pool, tick_lower, tick_upper);
+        pool.reserve_token += &token_added;
+        pool.reserve_base += &base_added;
+        self.update_cl_pool(&pool);
+
+        let mut position = ClPosition {
+            pool_id,
+            nonce: 0,
+            tick_lower,
+            tick_upper,
+            liquidity,
+            fee_growth_inside_token,
+            fee_growth_inside_base,
+        };
+        let nonce = self.send().esdt_nft_create_compact(&pool.position_token, &BigUint::from(1u64), &position);
+        position.nonce = nonce;
+        self.cl_position(pool_id, nonce).set(&position);
+
+        let caller = self.blockchain().get_caller();
+        if token_added < token_amount {
+            self.send().direct_esdt(&caller, &pool.token, 0, &(&token_amount - &token_added));
+        }
+        if base_added < base_amount {
+            self.send().direct_esdt(&caller, &pool.base_token, 0, &(&base_amount - &base_added));
+        }
+        self.send().direct_esdt(&caller, &pool.position_token, nonce, &BigUint::from(1u64));
+    }
+
+    #[payable("*")]
+    #[endpoint(removeClPosition)]
+    fn remove_cl_position(&self) {
+        let state = self.state().get();
+        require!(state == State::Active || state == State::Emergency, ERROR_NOT_ACTIVE);
+
+        let payment = self.call_value().single_esdt();
+        let mut pool = match self.get_cl_pool_by_position_token(&payment.token_identifier) {
+            Option::Some(pool) => pool,
+            Option::None => sc_panic!(ERROR_WRONG_PAYMENT),
+        };
+        if state == State::Active {
+            require!(pool.state != PairState::Inactive, ERROR_PAIR_NOT_ACTIVE);
+        }
+        require!(!self.cl_position(pool.id, payment.token_nonce).is_empty(), ERROR_POSITION_NOT_FOUND);
+
+        let mut position = self.cl_position(pool.id, payment.token_nonce).get();
+        let (fee_token, fee_base) = self.update_cl_position_fees(&pool, &mut position);
+        let (token_amount, base_amount) = self.get_cl_amounts_for_liquidity(
+            &pool.sqrt_price,
+            &self.get_sqrt_price_at_tick(position.tick_lower),
+            &self.get_sqrt_price_at_tick(position.tick_upper),
+            &position.liquidity,
+            false,
+        );
+        self.update_cl_tick(&pool, position.tick_lower, &position.liquidity, true, false);
+        self.update_cl_tick(&pool, position.tick_upper, &position.liquidity, false, false);
+        if pool.current_tick >= position.tick_lower && pool.current_tick < position.tick_upper {
+            pool.liquidity -= &position.liquidity;
+        }
+
+        let token_amount = token_amount + fee_token;
+        let base_amount = base_amount + fee_base;
+        pool.reserve_token -= &token_amount;
+        pool.reserve_base -= &base_amount;
+        self.update_cl_pool(&pool);
+        self.cl_position(pool.id, payment.token_nonce).clear();
+
+        self.send().esdt_local_burn(&pool.position_token, payment.token_nonce, &payment.amount);
+        let caller = self.blockchain().get_caller();
+        if token_amount > 0 {
+            self.send().direct_esdt(&caller, &pool.token, 0, &token_amount);
+        }
+        if base_amount > 0 {
+            self.send().direct_esdt(&caller, &pool.base_token, 0, &base_amount);
+        }
+    }
+
+    #[payable("*")]
+    #[endpoint(collectClFees)]
+    fn collect_cl_fees(&self) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+
+        let payment = self.call_value().single_esdt();
+        let mut pool = match self.get_cl_pool_by_position_token(&payment.token_identifier) {
+            Option::Some(pool) => pool,
+            Option::None => sc_panic!(ERROR_WRONG_PAYMENT),
+        };
+        require!(!self.cl_position(pool.id, payment.token_nonce).is_empty(), ERROR_POSITION_NOT_FOUND);
+
+        let mut position = self.cl_position(pool.id, payment.token_nonce).get();
+        let (fee_token, fee_base) = self.update_cl_position_fees(&pool, &mut position);
+        pool.reserve_token -= &fee_token;
+        pool.reserve_base -= &fee_base;
+        self.update_cl_pool(&pool);
+        self.cl_position(pool.id, payment.token_nonce).set(&position);
+
+        let caller = self.blockchain().get_caller();
+        if fee_token > 0 {
+            self.send().direct_esdt(&caller, &pool.token, 0, &fee_token);
+        }
+        if fee_base > 0 {
+            self.send().direct_esdt(&caller, &pool.base_token, 0, &fee_base);
+        }
+        self.send().direct_esdt(&caller, &pool.position_token, payment.token_nonce, &payment.amount);
+    }
+
+    // views
+    #[view(getClPools)]
+    fn get_cl_pools(&self) -> ManagedVec<ClPool<Self::Api>> {
+        let mut pools = ManagedVec::new();
+        for id in 0..self.last_pair_id().get() {
+            if self.cl_pool(id).is_empty() {
+                continue;
+            }
+
+            pools.push(self.cl_pool(id).get());
+        }
+
+        pools
+    }
+
+    #[view(getClPoolByTickers)]
+    fn get_cl_pool_by_tickers(&self, token1: &TokenIdentifier, token2: &TokenIdentifier) -> Option<ClPool<Self::Api>> {
+        for id in 0..self.last_pair_id().get() {
+            if self.cl_pool(id).is_empty() {
+                continue;
+            }
+
+            let pool = self.cl_pool(id).get();
+            if &pool.base_token == token1 && &pool.token == token2 {
+                return Some(pool);
+            }
+            if &pool.token == token1 && &pool.base_token == token2 {
+                return Some(pool);
+            }
+        }
+
+        None
+    }
+
+    #[view(getClPoolByPositionToken)]
+    fn get_cl_pool_by_position_token(&self, position_token: &TokenIdentifier) -> Option<ClPool<Self::Api>> {
+        for id in 0..self.last_pair_id().get() {
+            if self.cl_pool(id).is_empty() {
+                continue;
+            }
+
+            let pool = self.cl_pool(id).get();
+            if &pool.position_token == position_token {
+                return Some(pool);
+            }
+        }
+
+        None
+    }
+
+    #[view(getClPoolTicks)]
+    fn get_cl_pool_ticks(&self, pool_id: usize) -> ManagedVec<i32> {
+        let mut ticks = ManagedVec::new();
+        for tick in self.cl_pool_ticks(pool_id).iter() {
+            ticks.push(tick);
+        }
+
+        ticks
+    }
+
+    #[view(getClPositionFees)]
+    fn get_cl_position_fees(&self, pool_id: usize, nonce: u64) -> MultiValue2<BigUint, BigUint> {
+        require!(!self.cl_pool(pool_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+        require!(!self.cl_position(pool_id, nonce).is_empty(), ERROR_POSITION_NOT_FOUND);
+
+        let pool = self.cl_pool(pool_id).get();
+        let mut position = self.cl_position(pool_id, nonce).get();
+
+        self.update_cl_position_fees(&pool, &mut position).into()
+    }
+
+    // swaps
+    fn get_cl_amount(
+        &self,
+        token_in: &TokenIdentifier,
+        token_out: &TokenIdentifier,
+        amount: &BigUint,
+        exact_input: bool,
+    ) -> BigUint {
+        let mut pool = match self.get_cl_pool_by_tickers(token_in, token_out) {
+            Some(pool) => pool,
+            None => sc_panic!(ERROR_PAIR_NOT_FOUND),
+        };
+        let base_in = token_in == &pool.base_token;
+        let result = self.do_cl_swap(&mut pool, base_in, amount, exact_input, false);
+
+        if exact_input {
+            result.amount_out
+        } else {
+            result.amount_in
+        }
+    }
+
+    // fees are paid in the input token, the owner fee being left to the caller to send
+    fn swap_cl(
+        &self,
+        mut pool: ClPool<Self::Api>,
+        payment: &EsdtTokenPayment,
+        amount: &BigUint,
+        exact_input: bool,
+    ) -> ClSwapResult<Self::Api> {
+        require!(pool.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
+
+        let base_in = payment.token_identifier == pool.base_token;
+        let result = if exact_input {
+            let result = self.do_cl_swap(&mut pool, base_in, &payment.amount, true, true);
+            require!(&result.amount_out >= amount, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
+
+            result
+        } else {
+            let result = self.do_cl_swap(&mut pool, base_in, amount, false, true);
+            require!(result.amount_in > 0 && result.amount_in <= payment.amount, ERROR_INSUFFICIENT_INPUT_AMOUNT);
+
+            result
+        };
+
+        let added_in = &result.amount_in - &result.owner_fee;
+        if base_in {
+            pool.reserve_base += &added_in;
+            pool.reserve_token -= &result.amount_out;
+        } else {
+            pool.reserve_token += &added_in;
+            pool.reserve_base -= &result.amount_out;
+        }
+        self.update_cl_pool(&pool);
+
+        result
+    }
+
+    // same as getSwapQuote for pairs, except that the fees are in the input token and the reserves are the pool's
+    fn get_cl_swap_quote(
+        &self,
+        token_in: TokenIdentifier,
+        token_out: TokenIdentifier,
+        amount: BigUint,
+        kind: SwapKind,
+    ) -> SwapQuote<Self::Api> {
+        let mut pool = match self.get_cl_pool_by_tickers(&token_in, &token_out) {
+            Some(pool) => pool,
+            None => sc_panic!(ERROR_PAIR_NOT_FOUND),
+        };
+        require!(pool.liquidity > 0, ERROR_NO_LIQUIDITY);
+
+        let base_in = token_in == pool.base_token;
+        let price_before = self.get_cl_display_price(&pool);
+        let result = self.do_cl_swap(&mut pool, base_in, &amount, kind == SwapKind::FixedInput, false);
+        let price_after = self.get_cl_display_price(&pool);
+        let added_in = &result.amount_in - &result.owner_fee;
+        if base_in {
+            pool.reserve_base += &added_in;
+            pool.reserve_token -= &result.amount_out;
+        } else {
+            pool.reserve_token += &added_in;
+            pool.reserve_base -= &result.amount_out;
+        }
+
+        SwapQuote {
+            pair_id: pool.id,
+            token_in,
+            token_out,
+            price_impact: self.get_price_impact(&price_before, &price_after),
+            amount_in: result.amount_in,
+            amount_out: result.amount_out,
+            spot_price_before: price_before,
+            spot_price_after: price_after,
+            lp_fee: result.lp_fee,
+            owner_fee: result.owner_fee,
+            new_liquidity_token: pool.reserve_token,
+            new_liquidity_base: pool.reserve_base,
+        }
+    }
+
+    // sqrt_price^2 is the base token amount for one token unit, scaled by WAD, shown per whole token
+    fn get_cl_display_price(&self, pool: &ClPool<Self::Api>) -> BigUint {
+        let spot_price = &pool.sqrt_price * &pool.sqrt_price / WAD;
+
+        spot_price * &BigUint::from(10u64).pow(self.inspected_decimals(&pool.token).get())
+            / BigUint::from(10u64).pow(self.inspected_decimals(&pool.base_token).get())
+    }
+
+    // walks the initialized ticks in the swap direction, charging the fee on the input of each step
+    fn do_cl_swap(
+        &self,
+        pool: &mut ClPool<Self::Api>,
+        base_in: bool,
+        amount: &BigUint,
+        exact_input: bool,
+        commit: bool,
+    ) -> ClSwapResult<Self::Api> {
+        let total_fee = pool.lp_fee + pool.owner_fee;
+        let mut remaining = amount.clone();
+        let mut result = ClSwapResult {
+            amount_in: BigUint::zero(),
+            amount_out: BigUint::zero(),
+            lp_fee: BigUint::zero(),
+            owner_fee: BigUint::zero(),
+        };
+        while remaining > 0 {
+            let (next_tick, initialized) = self.get_next_cl_tick(pool, base_in);
+            let target = self.get_sqrt_price_at_tick(next_tick);
+            let step = self.compute_cl_swap_step(
+                &pool.sqrt_price,
+                &target,
+                &pool.liquidity,
+                &remaining,
+                base_in,
+                exact_input,
+                total_fee,
+            );
+            if exact_input {
+                remaining -= &(&step.amount_in + &step.fee);
+            } else {
+                remaining -= &step.amount_out;
+            }
+            result.amount_in += &(&step.amount_in + &step.fee);
+            result.amount_out += &step.amount_out;
+
+            if step.fee > 0 && total_fee > 0 {
+                let owner_fee = &step.fee * pool.owner_fee / total_fee;
+                let lp_fee = &step.fee - &owner_fee;
+                result.owner_fee += owner_fee;
+                result.lp_fee += &lp_fee;
+                if pool.liquidity > 0 {
+                    let fee_growth = lp_fee * WAD / &pool.liquidity;
+                    if base_in {
+                        pool.fee_growth_base += fee_growth;
+                    } else {
+                        pool.fee_growth_token += fee_growth;
+                    }
+                }
+            }
+
+            if step.sqrt_price == target {
+                if initialized {
+                    self.cross_cl_tick(pool, next_tick, base_in, commit);
+                } else {
+                    // the end of a bitmap word, or of the price range
+                    require!(next_tick != MIN_TICK && next_tick != MAX_TICK, ERROR_NOT_ENOUGH_CL_LIQUIDITY);
+
+                    pool.current_tick = if base_in { next_tick } else { next_tick - 1 };
+                    pool.sqrt_price = target;
+                }
+            } else {
+                pool.current_tick = self.get_tick_at_sqrt_price(&step.sqrt_price);
+                pool.sqrt_price = step.sqrt_price;
+            }
+        }
+
+        result
+    }
+
+    fn compute_cl_swap_step(
+        &self,
+        sqrt_price: &BigUint,
+        target: &BigUint,
+        liquidity: &BigUint,
+        remaining: &BigUint,
+        base_in: bool,
+        exact_input: bool,
+        total_fee: u64,
+    ) -> ClSwapStep<Self::Api> {
+        let (lower, upper) = if base_in { (sqrt_price, target) } else { (target, sqrt_price) };
+        let (new_sqrt_price, amount_out) = if exact_input {
+            let remaining_less_fee = remaining * (MAX_PERCENT - total_fee) / MAX_PERCENT;
+            let max_in = if base_in {
+                self.get_cl_base_delta(lower, upper, liquidity, true)
+            } else {
+                self.get_cl_token_delta(lower, upper, liquidity, true)
+            };
+            let new_sqrt_price = if remaining_less_fee >= max_in {
+                target.clone()
+            } else if base_in {
+                sqrt_price + &(&remaining_less_fee * WAD / liquidity)
+            } else {
+                let numerator = liquidity * WAD * sqrt_price;
+                let denominator = liquidity * WAD + &remaining_less_fee * sqrt_price;
+                self.div_round_up(&numerator, &denominator)
+            };
+            let amount_out = if base_in {
+                self.get_cl_token_delta(sqrt_price, &new_sqrt_price, liquidity, false)
+            } else {
+                self.get_cl_base_delta(&new_sqrt_price, sqrt_price, liquidity, false)
+            };
+
+            (new_sqrt_price, amount_out)
+        } else {
+            let max_out = if base_in {
+                self.get_cl_token_delta(lower, upper, liquidity, false)
+            } else {
+                self.get_cl_base_delta(lower, upper, liquidity, false)
+            };
+            if remaining >= &max_out {
+                (target.clone(), max_out)
+            } else if base_in {
+                let numerator = liquidity * WAD * sqrt_price;
+                let denominator = liquidity * WAD - remaining * sqrt_price;
+
+                (self.div_round_up(&numerator, &denominator), remaining.clone())
+            } else {
+                let delta = self.div_round_up(&(remaining * WAD), liquidity);
+
+                (sqrt_price - &delta, remaining.clone())
+            }
+        };
+
+        let mut amount_in = if base_in {
+            self.get_cl_base_delta(sqrt_price, &new_sqrt_price, liquidity, true)
+        } else {
+            self.get_cl_token_delta(&new_sqrt_price, sqrt_price, liquidity, true)
+        };
+        // a partially filled exact input step consumes the whole remaining amount, the difference being the fee
+        let fee = if exact_input && &new_sqrt_price != target {
+            if &amount_in > remaining {
+                amount_in = remaining.clone();
+            }
+
+            remaining - &amount_in
+        } else {
+            self.div_round_up(&(&amount_in * total_fee), &BigUint::from(MAX_PERCENT - total_fee))
+        };
+
+        ClSwapStep {
+            sqrt_price: new_sqrt_price,
+            amount_in,
+            amount_out,
+            fee,
+        }
+    }
+
+    fn cross_cl_tick(
+        &self,
+        pool: &mut ClPool<Self::Api>,
+        tick: i32,
+        upwards: bool,
+        commit: bool,
+    ) {
+        let mut info = self.cl_tick(pool.id, tick).get();
+        info.fee_growth_outside_token = &pool.fee_growth_token - &info.fee_growth_outside_token;
+        info.fee_growth_outside_base = &pool.fee_growth_base - &info.fee_growth_outside_base;
+        if commit {
+            self.cl_tick(pool.id, tick).set(&info);
+        }
+
+        if upwards {
+            pool.liquidity = &pool.liquidity + &info.liquidity_lower - &info.liquidity_upper;
+            pool.current_tick = tick;
+        } else {
+            pool.liquidity = &pool.liquidity + &info.liquidity_upper - &info.liquidity_lower;
+            pool.current_tick = tick - 1;
+        }
+        pool.sqrt_price = self.get_sqrt_price_at_tick(tick);
+    }
+
+    // the closest initialized tick above (or at or below, going down) the current one within the same bitmap word,
+    // otherwise the word's last tick in that direction, which is not initialized
+    fn get_next_cl_tick(&self, pool: &ClPool<Self::Api>, upwards: bool) -> (i32, bool) {
+        let spacing = pool.tick_spacing as i32;
+        let compressed = pool.current_tick.div_euclid(spacing);
+        let (next, initialized) = if upwards {
+            let (word, bit) = self.get_cl_bitmap_position(compressed + 1);
+            let masked = self.cl_tick_bitmap(pool.id, word).get() & !((1u64 << bit) - 1);
+            if masked != 0 {
+                ((compressed + 1 + (masked.trailing_zeros() - bit) as i32) * spacing, true)
+            } else {
+                ((compressed + 1 + (63 - bit) as i32) * spacing, false)
+            }
+        } else {
+            let (word, bit) = self.get_cl_bitmap_position(compressed);
+            let masked = self.cl_tick_bitmap(pool.id, word).get() & ((1u64 << bit) - 1 + (1u64 << bit));
+            if masked != 0 {
+                ((compressed - (bit - (63 - masked.leading_zeros())) as i32) * spacing, true)
+            } else {
+                ((compressed - bit as i32) * spacing, false)
+            }
+        };
+
+        if next >= MAX_TICK {
+            (MAX_TICK, initialized && next == MAX_TICK)
+        } else if next <= MIN_TICK {
+            (MIN_TICK, initialized && next == MIN_TICK)
+        } else {
+            (next, initialized)
+        }
+    }
+
+    // initialized ticks are tracked in words of 64 bits, one bit per multiple of the tick spacing
+    fn get_cl_bitmap_position(&self, compressed_tick: i32) -> (i32, u32) {
+        (compressed_tick >> 6, (compressed_tick & 63) as u32)
+    }
+
+    fn flip_cl_tick(&self, pool: &ClPool<Self::Api>, tick: i32) {
+        let (word, bit) = self.get_cl_bitmap_position(tick / pool.tick_spacing as i32);
+        self.cl_tick_bitmap(pool.id, word).update(|bitmap| *bitmap ^= 1u64 << bit);
+    }
+
+    // liquidity helpers
+    // stores the pool, booking the changes of its reserves
+    fn update_cl_pool(&self, pool: &ClPool<Self::Api>) {
+        let old_pool = self.cl_pool(pool.id).get();
+        self.book_reserve_change(&pool.token, &old_pool.reserve_token, &pool.reserve_token);
+        self.book_reserve_change(&pool.base_token, &old_pool.reserve_base, &pool.reserve_base);
+        self.cl_pool(pool.id).set(pool);
+    }
+
+    fn update_cl_tick(
+        &self,
+        pool: &ClPool<Self::Api>,
+        tick: i32,
+        liquidity: &BigUint,
+        is_lower: bool,
+        add: bool,
+    ) {
+        let mut info = if self.cl_tick(pool.id, tick).is_empty() {
+            self.cl_pool_ticks(pool.id).insert(tick);
+            self.flip_cl_tick(pool, tick);
+            // all fee growth so far is assumed to have happened below the current tick
+            if tick <= pool.current_tick {
+                ClTick {
+                    liquidity_lower: BigUint::zero(),
+                    liquidity_upper: BigUint::zero(),
+                    fee_growth_outside_token: pool.fee_growth_token.clone(),
+                    fee_growth_outside_base: pool.fee_growth_base.clone(),
+                }
+            } else {
+                ClTick {
+                    liquidity_lower: BigUint::zero(),
+                    liquidity_upper: BigUint::zero(),
+                    fee_growth_outside_token: BigUint::zero(),
+                    fee_growth_outside_base: BigUint::zero(),
+                }
+            }
+        } else {
+            self.cl_tick(pool.id, tick).get()
+        };
+
+        match (is_lower, add) {
+            (true, true) => info.liquidity_lower += liquidity,
+            (true, false) => info.liquidity_lower -= liquidity,
+            (false, true) => info.liquidity_upper += liquidity,
+            (false, false) => info.liquidity_upper -= liquidity,
+        }
+
+        if info.liquidity_lower == 0 && info.liquidity_upper == 0 {
+            self.cl_tick(pool.id, tick).clear();
+            self.cl_pool_ticks(pool.id).swap_remove(&tick);
+            self.flip_cl_tick(pool, tick);
+        } else {
+            self.cl_tick(pool.id, tick).set(&info);
+        }
+    }
+
+    // fee growth values wrap around 2^256, same as the uint256 counters of the original design
+    fn get_fee_growth_inside(
+        &self,
+        pool: &ClPool<Self::Api>,
+        tick_lower: i32,
+        tick_upper: i32,
+    ) -> (BigUint, BigUint) {
+        let lower = self.cl_tick(pool.id, tick_lower).get();
+        let upper = self.cl_tick(pool.id, tick_upper).get();
+        let (below_token, below_base) = if pool.current_tick >= tick_lower {
+            (lower.fee_growth_outside_token, lower.fee_growth_outside_base)
+        } else {
+            (
+                &pool.fee_growth_token - &lower.fee_growth_outside_token,
+                &pool.fee_growth_base - &lower.fee_growth_outside_base,
+            )
+        };
+        let (above_token, above_base) = if pool.current_tick < tick_upper {
+            (upper.fee_growth_outside_token, upper.fee_growth_outside_base)
+        } else {
+            (
+                &pool.fee_growth_token - &upper.fee_growth_outside_token,
+                &pool.fee_growth_base - &upper.fee_growth_outside_base,
+            )
+        };
+
+        let modulus = BigUint::from(2u64).pow(FEE_GROWTH_BITS);
+        let double_modulus = &modulus * 2u64;
+        (
+            (&pool.fee_growth_token + &double_modulus - below_token - above_token) % &modulus,
+            (&pool.fee_growth_base + &double_modulus - below_base - above_base) % &modulus,
+        )
+    }
+
+    fn update_cl_position_fees(
+        &self,
+        pool: &ClPool<Self::Api>,
+        position: &mut ClPosition<Self::Api>,
+    ) -> (BigUint, BigUint) {
+        let (inside_token, inside_base) = self.get_fee_growth_inside(pool, position.tick_lower, position.tick_upper);
+        let modulus = BigUint::from(2u64).pow(FEE_GROWTH_BITS);
+        let growth_token = (&inside_token + &modulus - &position.fee_growth_inside_token) % &modulus;
+        let growth_base = (&inside_base + &modulus - &position.fee_growth_inside_base) % &modulus;
+        position.fee_growth_inside_token = inside_token;
+        position.fee_growth_inside_base = inside_base;
+
+        (
+            &position.liquidity * &growth_token / WAD,
+            &position.liquidity * &growth_base / WAD,
+        )
+    }
+
+    fn get_cl_liquidity_for_amounts(
+        &self,
+        sqrt_price: &BigUint,
+        sqrt_price_lower: &BigUint,
+        sqrt_price_upper: &BigUint,
+        token_amount: &BigUint,
+        base_amount: &BigUint,
+    ) -> BigUint {
+        if sqrt_price <= sqrt_price_lower {
+            self.get_cl_liquidity_for_token(sqrt_price_lower, sqrt_price_upper, token_amount)
+        } else if sqrt_price < sqrt_price_upper {
+            let liquidity_token = self.get_cl_liquidity_for_token(sqrt_price, sqrt_price_upper, token_amount);
+            let liquidity_base = self.get_cl_liquidity_for_base(sqrt_price_lower, sqrt_price, base_amount);
+
+            core::cmp::min(liquidity_token, liquidity_base)
+        } else {
+            self.get_cl_liquidity_for_base(sqrt_price_lower, sqrt_price_upper, base_amount)
+        }
+    }
+
+    fn get_cl_amounts_for_liquidity(
+        &self,
+        sqrt_price: &BigUint,
+        sqrt_price_lower: &BigUint,
+        sqrt_price_upper: &BigUint,
+        liquidity: &BigUint,
+        round_up: bool,
+    ) -> (BigUint, BigUint) {
+        if sqrt_price <= sqrt_price_lower {
+            (self.get_cl_token_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up), BigUint::zero())
+        } else if sqrt_price < sqrt_price_upper {
+            (
+                self.get_cl_token_delta(sqrt_price, sqrt_price_upper, liquidity, round_up),
+                self.get_cl_base_delta(sqrt_price_lower, sqrt_price, liquidity, round_up),
+            )
+        } else {
+            (BigUint::zero(), self.get_cl_base_delta(sqrt_price_lower, sqrt_price_upper, liquidity, round_up))
+        }
+    }
+
+    fn get_cl_liquidity_for_token(&self, sqrt_price_a: &BigUint, sqrt_price_b: &BigUint, amount: &BigUint) -> BigUint {
+        amount * sqrt_price_a * sqrt_price_b / (BigUint::from(WAD) * (sqrt_price_b - sqrt_price_a))
+    }
+
+    fn get_cl_liquidity_for_base(&self, sqrt_price_a: &BigUint, sqrt_price_b: &BigUint, amount: &BigUint) -> BigUint {
+        amount * WAD / (sqrt_price_b - sqrt_price_a)
+    }
+
+    // token delta = L * (sqrt_price_b - sqrt_price_a) / (sqrt_price_a * sqrt_price_b)
+    fn get_cl_token_delta(
+        &self,
+        sqrt_price_a: &BigUint,
+        sqrt_price_b: &BigUint,
+        liquidity: &BigUint,
+        round_up: bool,
+    ) -> BigUint {
+        let numerator = liquidity * WAD * (sqrt_price_b - sqrt_price_a);
+        let denominator = sqrt_price_a * sqrt_price_b;
+        if round_up {
+            self.div_round_up(&numerator, &denominator)
+        } else {
+            numerator / denominator
+        }
+    }
+
+    // base delta = L * (sqrt_price_b - sqrt_price_a)
+    fn get_cl_base_delta(
+        &self,
+        sqrt_price_a: &BigUint,
+        sqrt_price_b: &BigUint,
+        liquidity: &BigUint,
+        round_up: bool,
+    ) -> BigUint {
+        let numerator = liquidity * &(sqrt_price_b - sqrt_price_a);
+        if round_up {
+            self.div_round_up(&numerator, &BigUint::from(WAD))
+        } else {
+            numerator / WAD
+        }
+    }
+
+    fn div_round_up(&self, numerator: &BigUint, denominator: &BigUint) -> BigUint {
+        (numerator + denominator - 1u64) / denominator
+    }
+
+    // tick math: sqrt_price = WAD * 1.0001 ^ (tick / 2)
+    fn get_sqrt_price_at_tick(&self, tick: i32) -> BigUint {
+        let exponent = BigUint::from(LN_TICK_BASE_WAD) * tick.unsigned_abs() as u64 / 2u64;
+        let sqrt_price = self.unwrap_math(exp_wad(&exponent));
+        if tick >= 0 {
+            sqrt_price
+        } else {
+            BigUint::from(WAD) * WAD / sqrt_price
+        }
+    }
+
+    fn get_tick_at_sqrt_price(&self, sqrt_price: &BigUint) -> i32 {
+        let wad = BigUint::from(WAD);
+        let mut tick = if sqrt_price >= &wad {
+            let ln = ln_wad(sqrt_price);
+            (ln * 2u64 / LN_TICK_BASE_WAD).to_u64().unwrap_or(MAX_TICK as u64) as i32
+        } else {
+            let ln = ln_wad(&(&wad * &wad / sqrt_price));
+            -((ln * 2u64 / LN_TICK_BASE_WAD).to_u64().unwrap_or(MAX_TICK as u64) as i32) - 1
+        };
+        tick = tick.clamp(MIN_TICK, MAX_TICK);
+
+        // the fixed point logarithm can be off by one tick, so settle on the exact one
+        while tick < MAX_TICK && &self.get_sqrt_price_at_tick(tick + 1) <= sqrt_price {
+            tick += 1;
+        }
+        while tick > MIN_TICK && &self.get_sqrt_price_at_tick(tick) > sqrt_price {
+            tick -= 1;
+        }
+
+        tick
+    }
+
+    // storage
+    #[storage_mapper("cl_pool_ticks")]
+    fn cl_pool_ticks(&self, pool_id: usize) -> UnorderedSetMapper<i32>;
+
+    #[storage_mapper("cl_tick_bitmap")]
+    fn cl_tick_bitmap(&self, pool_id: usize, word: i32) -> SingleValueMapper<u64>;
+
+    #[view(getClTick)]
+    #[storage_mapper("cl_ticks")]
+    fn cl_tick(&self, pool_id: usize, tick: i32) -> SingleValueMapper<ClTick<Self::Api>>;
+
+    #[view(getClPosition)]
+    #[storage_mapper("cl_positions")]
+    fn cl_position(&self, pool_id: usize, nonce: u64) -> SingleValueMapper<ClPosition<Self::Api>>;
+}
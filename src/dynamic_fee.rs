@@ -0,0 +1,121 @@
+use tfn_dex::common::{consts::*, errors::*};
+
+use crate::common::{self, config::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait DynamicFeeModule:
+common::config::ConfigModule
++super::helpers::HelpersModule
++super::observations::ObservationsModule
++super::pair_policy::PairPolicyModule
+{
+    #[endpoint(setPairDynamicFee)]
+    fn set_pair_dynamic_fee(
+        &self,
+        id: usize,
+        min_fee: u64,
+        max_fee: u64,
+        window: u64,
+        sensitivity: u64,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let pair = self.pair(id).get();
+        require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(min_fee <= max_fee && max_fee + pair.owner_fee < MAX_PERCENT, ERROR_WRONG_FEES);
+        // the effective LP fee moves between the bounds, so both should be allowed for the owner's tier
+        self.check_fee_range(&pair.owner, min_fee, pair.owner_fee);
+        self.check_fee_range(&pair.owner, max_fee, pair.owner_fee);
+        require!(window > 0, ERROR_WRONG_FEE_WINDOW);
+
+        self.dynamic_fee(id).set(DynamicFee {
+            min_fee,
+            max_fee,
+            window,
+            sensitivity,
+        });
+        let observation = self.get_current_price_observation(id);
+        self.fee_window_previous(id).set(&observation);
+        self.fee_window_start(id).set(&observation);
+    }
+
+    #[endpoint(disablePairDynamicFee)]
+    fn disable_pair_dynamic_fee(&self, id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let pair = self.pair(id).get();
+        require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(!self.dynamic_fee(id).is_empty(), ERROR_DYNAMIC_FEE_NOT_SET);
+
+        self.dynamic_fee(id).clear();
+        self.fee_window_previous(id).clear();
+        self.fee_window_start(id).clear();
+    }
+
+    #[view(getEffectiveLpFee)]
+    fn get_effective_lp_fee_view(&self, id: usize) -> u64 {
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        self.get_effective_lp_fee(&self.pair(id).get())
+    }
+
+    // the LP fee grows with the deviation of the spot price from the TWAP of the last one or two windows
+    fn get_effective_lp_fee(&self, pair: &Pair<Self::Api>) -> u64 {
+        if self.dynamic_fee(pair.id).is_empty() {
+            return pair.lp_fee;
+        }
+
+        let config = self.dynamic_fee(pair.id).get();
+        if pair.liquidity_token == 0 || pair.liquidity_base == 0 {
+            return config.min_fee;
+        }
+
+        let reference = self.get_twap_since(pair.id, &self.fee_window_previous(pair.id).get());
+        if reference == 0 {
+            return config.min_fee;
+        }
+
+        let spot = self.get_spot_price(pair);
+        let deviation = if spot > reference {
+            &spot - &reference
+        } else {
+            &reference - &spot
+        };
+        let volatility = deviation * MAX_PERCENT / reference;
+        let fee = volatility * config.sensitivity / MAX_PERCENT + config.min_fee;
+        if fee > config.max_fee {
+            config.max_fee
+        } else {
+            fee.to_u64().unwrap()
+        }
+    }
+
+    fn update_fee_window(&self, pair_id: usize) {
+        if self.dynamic_fee(pair_id).is_empty() {
+            return;
+        }
+
+        let config = self.dynamic_fee(pair_id).get();
+        let window_start = self.fee_window_start(pair_id).get();
+        if self.blockchain().get_block_timestamp() < window_start.timestamp + config.window {
+            return;
+        }
+
+        self.fee_window_previous(pair_id).set(window_start);
+        self.fee_window_start(pair_id).set(self.get_current_price_observation(pair_id));
+    }
+
+    #[view(getPairDynamicFee)]
+    #[storage_mapper("dynamic_fees")]
+    fn dynamic_fee(&self, pair_id: usize) -> SingleValueMapper<DynamicFee>;
+
+    #[storage_mapper("fee_window_previous")]
+    fn fee_window_previous(&self, pair_id: usize) -> SingleValueMapper<PriceObservation<Self::Api>>;
+
+    #[storage_mapper("fee_window_start")]
+    fn fee_window_start(&self, pair_id: usize) -> SingleValueMapper<PriceObservation<Self::Api>>;
+}
@@ -0,0 +1,295 @@
+use tfn_dex::common::errors::*;
+
+use crate::common::{self, config::*, consts::*, errors::*};
+use tfn_platform::common::config::ProxyTrait as _;
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait FarmModule:
+common::config::ConfigModule
++super::helpers::HelpersModule
++super::reserves::ReservesModule
++super::liquidity::LiquidityModule
++super::swap::SwapModule
+{
+    // funds the pair's farm with governance tokens, creating it if needed, and sets its emission rate
+    #[payable("*")]
+    #[endpoint(fundFarm)]
+    fn fund_farm(&self, pair_id: usize, reward_per_block: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        let payment = self.call_value().single_esdt();
+        let mut farm = self.get_or_create_farm(pair_id);
+        require!(payment.token_identifier == farm.reward_token, ERROR_WRONG_PAYMENT);
+        require!(payment.amount > 0 && reward_per_block > 0, ERROR_ZERO_AMOUNT);
+
+        farm.reward_per_block = reward_per_block;
+        farm.reward_reserve += &payment.amount;
+        self.increase_token_reserve(&farm.reward_token, &payment.amount);
+        self.farm(pair_id).set(farm);
+    }
+
+    // stops the emissions and sends the rewards not distributed yet back to the caller
+    #[endpoint(stopFarm)]
+    fn stop_farm(&self, pair_id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.farm(pair_id).is_empty(), ERROR_FARM_NOT_FOUND);
+        let caller = self.blockchain().get_caller();
+        self.only_subscriber(&caller);
+
+        let mut farm = self.get_updated_farm(pair_id);
+        let reward_reserve = core::mem::replace(&mut farm.reward_reserve, BigUint::zero());
+        farm.reward_per_block = BigUint::zero();
+        self.farm(pair_id).set(&farm);
+
+        if reward_reserve > 0 {
+            self.decrease_token_reserve(&farm.reward_token, &reward_reserve);
+            self.send().direct_esdt(&caller, &farm.reward_token, 0, &reward_reserve);
+        }
+    }
+
+    #[payable("*")]
+    #[endpoint(stakeLp)]
+    fn stake_lp(&self) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+
+        let payment = self.call_value().single_esdt();
+        let pair = match self.get_pair_by_lp_token(&payment.token_identifier) {
+            Option::Some(pair) => pair,
+            Option::None => sc_panic!(ERROR_WRONG_PAYMENT),
+        };
+        require!(pair.state != PairState::Delisted, ERROR_PAIR_DELISTED);
+        require!(!self.farm(pair.id).is_empty(), ERROR_FARM_NOT_FOUND);
+        require!(payment.amount > 0, ERROR_ZERO_AMOUNT);
+
+        let caller = self.blockchain().get_caller();
+        let mut farm = self.get_updated_farm(pair.id);
+        let mut position = self.get_updated_farm_position(&farm, &caller);
+        self.stake(&mut farm, &mut position, &payment.amount);
+        self.farm(pair.id).set(farm);
+        self.farm_position(pair.id, &caller).set(position);
+    }
+
+    // unstaking also pays out the pending rewards and remains possible in emergency mode
+    #[endpoint(unstakeLp)]
+    fn unstake_lp(&self, pair_id: usize, amount: BigUint) {
+        let state = self.state().get();
+        require!(state == State::Active || state == State::Emergency, ERROR_NOT_ACTIVE);
+        require!(!self.farm(pair_id).is_empty(), ERROR_FARM_NOT_FOUND);
+
+        let caller = self.blockchain().get_caller();
+        let mut farm = self.get_updated_farm(pair_id);
+        let mut position = self.get_updated_farm_position(&farm, &caller);
+        require!(amount > 0, ERROR_ZERO_AMOUNT);
+        require!(position.staked >= amount, ERROR_NOT_STAKED);
+
+        position.staked -= &amount;
+        farm.total_staked -= &amount;
+        farm.total_weight -= &amount;
+        self.decrease_token_reserve(&farm.lp_token, &amount);
+        let rewards = self.take_farm_rewards(&mut farm, &mut position);
+        self.farm(pair_id).set(&farm);
+        self.store_farm_position(pair_id, &caller, &position);
+
+        self.send().direct_esdt(&caller, &farm.lp_token, 0, &amount);
+        if rewards > 0 {
+            self.send().direct_esdt(&caller, &farm.reward_token, 0, &rewards);
+        }
+    }
+
+    #[endpoint(claimFarmRewards)]
+    fn claim_farm_rewards(&self, pair_id: usize) {
+        let state = self.state().get();
+        require!(state == State::Active || state == State::Emergency, ERROR_NOT_ACTIVE);
+        require!(!self.farm(pair_id).is_empty(), ERROR_FARM_NOT_FOUND);
+
+        let caller = self.blockchain().get_caller();
+        let mut farm = self.get_updated_farm(pair_id);
+        let mut position = self.get_updated_farm_position(&farm, &caller);
+        let rewards = self.take_farm_rewards(&mut farm, &mut position);
+        require!(rewards > 0, ERROR_NO_REWARDS);
+
+        self.farm(pair_id).set(&farm);
+        self.store_farm_position(pair_id, &caller, &position);
+        self.send().direct_esdt(&caller, &farm.reward_token, 0, &rewards);
+    }
+
+    // swaps half of the rewards for the pair's other token, adds both as liquidity and stakes the new LP tokens
+    #[endpoint(compoundFarmRewards)]
+    fn compound_farm_rewards(&self, pair_id: usize, min_lp_amount: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.farm(pair_id).is_empty(), ERROR_FARM_NOT_FOUND);
+        require!(!self.pair(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let caller = self.blockchain().get_caller();
+        let mut farm = self.get_updated_farm(pair_id);
+        let mut position = self.get_updated_farm_position(&farm, &caller);
+        let rewards = self.take_farm_rewards(&mut farm, &mut position);
+        require!(rewards > 0, ERROR_NO_REWARDS);
+
+        let pair = self.pair(pair_id).get();
+        let other_token = if pair.base_token == farm.reward_token {
+            pair.token
+        } else {
+            require!(pair.token == farm.reward_token, ERROR_CANNOT_COMPOUND);
+
+            pair.base_token
+        };
+        let half = &rewards / 2u64;
+        let swapped = self.swap_fixed_input_internal(
+            &EsdtTokenPayment::new(farm.reward_token.clone(), 0, half.clone()),
+            &other_token,
+            &BigUint::zero(),
+            &None,
+        );
+        let kept = &rewards - &half;
+
+        let mut pair = self.pair(pair_id).get();
+        let (token_amount, base_amount) = if pair.token == farm.reward_token {
+            (kept, swapped.amount)
+        } else {
+            (swapped.amount, kept)
+        };
+        let (lp_token_amount, token_added, base_added) = self.add_liquidity_internal(&mut pair, &token_amount, &base_amount);
+        require!(lp_token_amount >= min_lp_amount, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
+
+        self.send().esdt_local_mint(&pair.lp_token, 0, &lp_token_amount);
+        self.stake(&mut farm, &mut position, &lp_token_amount);
+        self.farm(pair_id).set(&farm);
+        self.farm_position(pair_id, &caller).set(position);
+
+        // return surplus tokens
+        if token_added < token_amount {
+            self.send().direct_esdt(&caller, &pair.token, 0, &(&token_amount - &token_added));
+        }
+        if base_added < base_amount {
+            self.send().direct_esdt(&caller, &pair.base_token, 0, &(&base_amount - &base_added));
+        }
+    }
+
+    // views
+    #[view(getFarms)]
+    fn get_farms(&self) -> ManagedVec<Farm<Self::Api>> {
+        let mut farms = ManagedVec::new();
+        for id in 0..self.last_pair_id().get() {
+            if self.farm(id).is_empty() {
+                continue;
+            }
+
+            farms.push(self.get_updated_farm(id));
+        }
+
+        farms
+    }
+
+    #[view(getPendingFarmRewards)]
+    fn get_pending_farm_rewards(&self, pair_id: usize, address: ManagedAddress) -> BigUint {
+        if self.farm(pair_id).is_empty() {
+            return BigUint::zero();
+        }
+
+        let farm = self.get_updated_farm(pair_id);
+        self.get_updated_farm_position(&farm, &address).pending_rewards
+    }
+
+    // helpers
+    fn get_governance_token(&self) -> TokenIdentifier {
+        self.platform_contract_proxy()
+            .contract(self.platform_sc().get())
+            .governance_token()
+            .execute_on_dest_context()
+    }
+
+    // a farm without emissions is created for pairs that get staked or locked LP tokens before being funded
+    fn get_or_create_farm(&self, pair_id: usize) -> Farm<Self::Api> {
+        if !self.farm(pair_id).is_empty() {
+            return self.get_updated_farm(pair_id);
+        }
+
+        Farm {
+            pair_id,
+            lp_token: self.pair(pair_id).get().lp_token,
+            reward_token: self.get_governance_token(),
+            reward_per_block: BigUint::zero(),
+            reward_reserve: BigUint::zero(),
+            rewards_unclaimed: BigUint::zero(),
+            total_staked: BigUint::zero(),
+            total_weight: BigUint::zero(),
+            reward_per_share: BigUint::zero(),
+            last_reward_block: self.blockchain().get_block_nonce(),
+        }
+    }
+
+    // distributes the rewards emitted since the last update to the stakers, as long as the reserve lasts
+    fn get_updated_farm(&self, pair_id: usize) -> Farm<Self::Api> {
+        let mut farm = self.farm(pair_id).get();
+        let block = self.blockchain().get_block_nonce();
+        if block <= farm.last_reward_block {
+            return farm;
+        }
+
+        if farm.total_weight > 0 {
+            let mut rewards = &farm.reward_per_block * (block - farm.last_reward_block);
+            if rewards > farm.reward_reserve {
+                rewards = farm.reward_reserve.clone();
+            }
+            farm.reward_per_share += &rewards * WAD / &farm.total_weight;
+            farm.reward_reserve -= &rewards;
+            farm.rewards_unclaimed += rewards;
+        }
+        farm.last_reward_block = block;
+
+        farm
+    }
+
+    fn get_updated_farm_position(&self, farm: &Farm<Self::Api>, address: &ManagedAddress) -> FarmPosition<Self::Api> {
+        if self.farm_position(farm.pair_id, address).is_empty() {
+            return FarmPosition {
+                staked: BigUint::zero(),
+                reward_per_share: farm.reward_per_share.clone(),
+                pending_rewards: BigUint::zero(),
+            };
+        }
+
+        let mut position = self.farm_position(farm.pair_id, address).get();
+        position.pending_rewards += &position.staked * &(&farm.reward_per_share - &position.reward_per_share) / WAD;
+        position.reward_per_share = farm.reward_per_share.clone();
+
+        position
+    }
+
+    fn stake(&self, farm: &mut Farm<Self::Api>, position: &mut FarmPosition<Self::Api>, amount: &BigUint) {
+        position.staked += amount;
+        farm.total_staked += amount;
+        farm.total_weight += amount;
+        self.increase_token_reserve(&farm.lp_token, amount);
+    }
+
+    fn take_farm_rewards(&self, farm: &mut Farm<Self::Api>, position: &mut FarmPosition<Self::Api>) -> BigUint {
+        let rewards = core::mem::replace(&mut position.pending_rewards, BigUint::zero());
+        farm.rewards_unclaimed -= &rewards;
+        self.decrease_token_reserve(&farm.reward_token, &rewards);
+
+        rewards
+    }
+
+    fn store_farm_position(&self, pair_id: usize, address: &ManagedAddress, position: &FarmPosition<Self::Api>) {
+        if position.staked == 0 && position.pending_rewards == 0 {
+            self.farm_position(pair_id, address).clear();
+        } else {
+            self.farm_position(pair_id, address).set(position);
+        }
+    }
+
+    // storage
+    #[view(getFarm)]
+    #[storage_mapper("farms")]
+    fn farm(&self, pair_id: usize) -> SingleValueMapper<Farm<Self::Api>>;
+
+    #[view(getFarmPosition)]
+    #[storage_mapper("farm_positions")]
+    fn farm_position(&self, pair_id: usize, address: &ManagedAddress) -> SingleValueMapper<FarmPosition<Self::Api>>;
+}
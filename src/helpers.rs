@@ -1,105 +1,94 @@
 use tfn_dex::common::consts::*;
 
-use crate::common::config;
+use crate::common::{config::{self, *}, consts::*, errors::*};
+
+pub use tfn_test_dex_core::CurveParams;
+use tfn_test_dex_core::{stable_swap::{compute_d, get_precision_rate}, MathError};
 
 multiversx_sc::imports!();
 
 #[multiversx_sc::module]
 pub trait HelpersModule:
 config::ConfigModule
++super::stable_swap::StableSwapModule
 {
-    fn quote(
-        &self,
-        token_amount: &BigUint,
-        token_liquidity: &BigUint,
-        base_liquidity: &BigUint,
-    ) -> BigUint {
-        &(token_amount * base_liquidity) / token_liquidity
+    fn get_curve_params(&self, pair: &Pair<Self::Api>, base_in: bool) -> CurveParams {
+        let (weight_in, weight_out) = if base_in {
+            (pair.base_weight, pair.token_weight)
+        } else {
+            (pair.token_weight, pair.base_weight)
+        };
+        let (decimals_in, decimals_out) = if base_in {
+            (pair.base_decimals, pair.token_decimals)
+        } else {
+            (pair.token_decimals, pair.base_decimals)
+        };
+
+        CurveParams {
+            curve: pair.curve.into(),
+            amp: self.get_current_amp(&pair.amp),
+            weight_in,
+            weight_out,
+            rate_in: get_precision_rate(decimals_in),
+            rate_out: get_precision_rate(decimals_out),
+        }
     }
 
-    fn get_amount_out_no_fee(
-        &self,
-        amount_in: &BigUint,
-        liquidity_in: &BigUint,
-        liquidity_out: &BigUint,
-    ) -> BigUint {
-        let numerator = amount_in * liquidity_out;
-        let denominator = liquidity_in + amount_in;
-
-        numerator / denominator
+    // marginal price of one token unit in base token units, scaled by WAD
+    fn get_spot_price(&self, pair: &Pair<Self::Api>) -> BigUint {
+        match pair.curve {
+            Curve::ConstantProduct => &pair.liquidity_base * WAD / &pair.liquidity_token,
+            Curve::Weighted => {
+                &pair.liquidity_base * pair.token_weight * WAD / (&pair.liquidity_token * pair.base_weight)
+            }
+            Curve::StableSwap => {
+                // ratio of the invariant's partial derivatives: (4Ann*x^2*y^2 + D^3*y) / (4Ann*x^2*y^2 + D^3*x),
+                // computed on the reserves brought to a common precision, then converted back to token units
+                let amp = self.get_current_amp(&pair.amp);
+                let token_rate = get_precision_rate(pair.token_decimals);
+                let base_rate = get_precision_rate(pair.base_decimals);
+                let x = &pair.liquidity_token * token_rate;
+                let y = &pair.liquidity_base * base_rate;
+                let d = compute_d(&x, &y, amp);
+                let d_cubed = &d * &d * &d;
+                let product = &x * &y;
+                let common = &product * &product * (4 * amp * STABLE_SWAP_COINS);
+                let numerator = (&common + &(&d_cubed * &y)) * WAD * token_rate;
+                let denominator = (common + d_cubed * &x) * base_rate;
+
+                numerator / denominator
+            }
+        }
     }
 
-    fn get_amount_in_no_fee(
-        &self,
-        amount_out: &BigUint,
-        liquidity_in: &BigUint,
-        liquidity_out: &BigUint,
-    ) -> BigUint {
-        let numerator = liquidity_in * amount_out;
-        let denominator = liquidity_out - amount_out;
-
-        (numerator / denominator) + &BigUint::from(1u64)
+    // price of one whole token in whole base tokens, scaled by WAD
+    fn get_display_price(&self, pair: &Pair<Self::Api>, spot_price: &BigUint) -> BigUint {
+        spot_price * &BigUint::from(10u64).pow(pair.token_decimals) / BigUint::from(10u64).pow(pair.base_decimals)
     }
 
-    fn get_amount_out(
-        &self,
-        amount_in: &BigUint,
-        liquidity_in: &BigUint,
-        liquidity_out: &BigUint,
-        fee_in: bool,
-        total_fee: u64,
-    ) -> BigUint {
-        if fee_in {
-            let amount_in_with_fee = amount_in * (MAX_PERCENT - total_fee);
-            let numerator = &amount_in_with_fee * liquidity_out;
-            let denominator = liquidity_in * MAX_PERCENT + amount_in_with_fee;
-
-            numerator / denominator
-        } else {
-            let amount_out_no_fee = self.get_amount_out_no_fee(amount_in, liquidity_in, liquidity_out);
-
-            amount_out_no_fee * (MAX_PERCENT - total_fee) / MAX_PERCENT
+    // relative change between two prices, in basis points
+    fn get_price_impact(&self, price_before: &BigUint, price_after: &BigUint) -> u64 {
+        // a price moving away from zero has no finite relative change, it counts as the largest impact
+        if price_before == &BigUint::zero() {
+            return if price_after == &BigUint::zero() { 0 } else { u64::MAX };
         }
-    }
 
-    fn get_amount_in(
-        &self,
-        amount_out: &BigUint,
-        liquidity_in: &BigUint,
-        liquidity_out: &BigUint,
-        fee_in: bool,
-        total_fee: u64,
-    ) -> BigUint {
-        if fee_in {
-            let numerator = amount_out * liquidity_in * MAX_PERCENT;
-            let denominator = (liquidity_out - amount_out) * (MAX_PERCENT - total_fee);
-
-            (numerator / denominator) + 1u64
+        let change = if price_after > price_before {
+            price_after - price_before
         } else {
-            let amount_out_with_fee = amount_out * MAX_PERCENT / (MAX_PERCENT - total_fee);
+            price_before - price_after
+        };
 
-            self.get_amount_in_no_fee(&amount_out_with_fee, liquidity_in, liquidity_out)
-        }
+        (change * MAX_PERCENT / price_before).to_u64().unwrap_or(u64::MAX)
     }
 
-    // returns lp fee, owner fee, total fee calculated from amount
-    fn get_fee_amounts(
-        &self, amount: &BigUint,
-        is_input: bool,
-        lp_fee: u64,
-        owner_fee: u64,
-    ) -> (BigUint, BigUint, BigUint) {
-        let total_fee = lp_fee + owner_fee;
-
-        if is_input {
-            (amount * lp_fee / MAX_PERCENT, amount * owner_fee / MAX_PERCENT, amount * total_fee / MAX_PERCENT)
-        } else {
-            let total_fee_amount = amount * total_fee / (MAX_PERCENT - total_fee);
-            let lp_fee_amount = &total_fee_amount * lp_fee / total_fee;
-            let owner_fee_amount = &total_fee_amount - &lp_fee_amount;
-
-            (lp_fee_amount, owner_fee_amount, total_fee_amount)
+    // the pricing math lives in the core crate, its errors are turned into contract errors here
+    fn unwrap_math<T>(&self, result: Result<T, MathError>) -> T {
+        match result {
+            Ok(value) => value,
+            Err(MathError::PowOverflow) => sc_panic!(ERROR_POW_OVERFLOW),
+            Err(MathError::InvariantViolated) => sc_panic!(ERROR_INVARIANT_VIOLATED),
+            Err(MathError::NotEnoughLiquidity) => sc_panic!(ERROR_NOT_ENOUGH_LIQUIDITY),
         }
     }
-
 }
@@ -0,0 +1,51 @@
+use crate::common::{self, config::*, consts::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait HistoryModule:
+common::config::ConfigModule
+{
+    // keeps the last MAX_TRADE_HISTORY trades of the caller, overwriting the oldest one once the history is full
+    fn record_trade(&self, pair_id: usize, kind: TradeKind, token_amount: &BigUint, base_amount: &BigUint) {
+        let caller = self.blockchain().get_caller();
+        let price = if token_amount > &0 {
+            base_amount * WAD / token_amount
+        } else {
+            BigUint::zero()
+        };
+        let count = self.trade_count(&caller).get();
+        self.trade_record(&caller, count % MAX_TRADE_HISTORY).set(TradeRecord {
+            pair_id,
+            kind,
+            token_amount: token_amount.clone(),
+            base_amount: base_amount.clone(),
+            price,
+            timestamp: self.blockchain().get_block_timestamp(),
+        });
+        self.trade_count(&caller).set(count + 1);
+    }
+
+    // newest first, skipping the `from` most recent trades
+    #[view(getTradeHistory)]
+    fn get_trade_history(&self, address: ManagedAddress, from: u64, size: u64) -> ManagedVec<TradeRecord<Self::Api>> {
+        let count = self.trade_count(&address).get();
+        let stored = core::cmp::min(count, MAX_TRADE_HISTORY);
+        let mut records = ManagedVec::new();
+        let mut offset = from;
+        while offset < stored && offset < from.saturating_add(size) {
+            records.push(self.trade_record(&address, (count - 1 - offset) % MAX_TRADE_HISTORY).get());
+            offset += 1;
+        }
+
+        records
+    }
+
+    // the number of trades ever recorded for the address, including the ones overwritten
+    #[view(getTradeCount)]
+    #[storage_mapper("trade_count")]
+    fn trade_count(&self, address: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("trade_records")]
+    fn trade_record(&self, address: &ManagedAddress, index: u64) -> SingleValueMapper<TradeRecord<Self::Api>>;
+}
@@ -6,9 +6,24 @@ pub mod common;
 pub mod helpers;
 pub mod swap;
 pub mod liquidity;
-
-use common::{config::*, errors::*};
-use tfn_platform::common::config::ProxyTrait as _;
+pub mod stable_swap;
+pub mod concentrated;
+pub mod observations;
+pub mod dynamic_fee;
+pub mod reserves;
+pub mod circuit_breaker;
+pub mod farm;
+pub mod lock;
+pub mod wegld;
+pub mod token_risk;
+pub mod referral;
+pub mod history;
+pub mod oracle;
+pub mod oracle_proxy;
+pub mod portfolio;
+pub mod pair_policy;
+
+use common::{config::*, consts::*, errors::*};
 use tfn_dex::common::{errors::*, consts::*};
 
 #[multiversx_sc::contract]
@@ -17,9 +32,25 @@ common::config::ConfigModule
 +helpers::HelpersModule
 +swap::SwapModule
 +liquidity::LiquidityModule
++stable_swap::StableSwapModule
++concentrated::ConcentratedModule
++observations::ObservationsModule
++dynamic_fee::DynamicFeeModule
++reserves::ReservesModule
++circuit_breaker::CircuitBreakerModule
++farm::FarmModule
++lock::LockModule
++wegld::WegldModule
++token_risk::TokenRiskModule
++referral::ReferralModule
++history::HistoryModule
++oracle::OracleModule
++portfolio::PortfolioModule
++pair_policy::PairPolicyModule
 {
     #[init]
     fn init(&self) {
+        self.storage_version().set(STORAGE_VERSION);
         let caller = self.blockchain().get_caller();
         if self.blockchain().is_smart_contract(&caller) {
             self.platform_sc().set(&caller);
@@ -34,6 +65,11 @@ common::config::ConfigModule
 
     #[upgrade]
     fn upgrade(&self) {
+        if self.storage_version().get() == 0 {
+            self.migrate_legacy_pairs();
+        }
+        self.storage_version().set(STORAGE_VERSION);
+        self.rebuild_token_reserves();
     }
 
     #[payable("EGLD")]
@@ -44,14 +80,43 @@ common::config::ConfigModule
         token: TokenIdentifier,
         lp_fee: u64,
         owner_fee: u64,
+        curve: Curve,
+        amp: u64,
+        token_weight: u64,
     ) {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
 
         let caller = self.blockchain().get_caller();
         self.check_whitelisted(&caller);
+        self.check_pair_creation_policy(&caller, lp_fee, owner_fee);
         require!(self.base_tokens().contains(&base_token), ERROR_WRONG_BASE_TOKEN);
         require!(base_token != token, ERROR_WRONG_BASE_TOKEN);
+        require!(!self.denied_tokens().contains(&token), ERROR_TOKEN_DENIED);
+        self.check_token_transferable(&token);
+        self.check_token_risk(&token, &base_token);
         require!(self.get_pair_by_tickers(&token, &base_token).is_none(), ERROR_PAIR_EXISTS);
+        require!(self.get_cl_pool_by_tickers(&token, &base_token).is_none(), ERROR_PAIR_EXISTS);
+        require!(lp_fee + owner_fee < MAX_PERCENT, ERROR_WRONG_FEES);
+        let amp = match curve {
+            Curve::ConstantProduct => 0,
+            Curve::StableSwap => {
+                require!((MIN_AMP..=MAX_AMP).contains(&amp), ERROR_WRONG_AMP);
+
+                amp
+            }
+            Curve::Weighted => 0,
+        };
+        let (token_weight, base_weight) = match curve {
+            Curve::Weighted => {
+                require!(
+                    token_weight >= MIN_TOKEN_WEIGHT && token_weight <= MAX_PERCENT - MIN_TOKEN_WEIGHT,
+                    ERROR_WRONG_WEIGHTS
+                );
+
+                (token_weight, MAX_PERCENT - token_weight)
+            }
+            _ => (MAX_PERCENT / 2, MAX_PERCENT / 2),
+        };
 
         let mut lp_ticker = token.ticker().concat(base_token.ticker());
         let prefix_suffix_len = LP_TOKEN_PREFIX.len() + LP_TOKEN_SUFFIX.len();
@@ -75,7 +140,7 @@ common::config::ConfigModule
                 EsdtTokenType::Fungible,
                 LP_TOKEN_DECIMALS,
             )
-            .with_callback(self.callbacks().lp_token_issue_callback(caller, &base_token, &token, lp_fee, owner_fee))
+            .with_callback(self.callbacks().lp_token_issue_callback(caller, &base_token, &token, lp_fee, owner_fee, curve, amp, token_weight, base_weight))
             .async_call_and_exit();
     }
 
@@ -87,15 +152,25 @@ common::config::ConfigModule
         token: &TokenIdentifier,
         lp_fee: u64,
         owner_fee: u64,
+        curve: Curve,
+        amp: u64,
+        token_weight: u64,
+        base_weight: u64,
         #[call_result] result: ManagedAsyncCallResult<TokenIdentifier>,
     ) {
         match result {
             ManagedAsyncCallResult::Ok(lp_token) => {
                 let id = self.last_pair_id().get();
+                let now = self.blockchain().get_block_timestamp();
+                let state = if self.pair_approval_required().get() {
+                    PairState::PendingApproval
+                } else {
+                    PairState::ActiveNoSwap
+                };
                 let pair = Pair {
                     id,
                     owner: caller,
-                    state: PairState::ActiveNoSwap,
+                    state,
                     token: token.clone(),
                     base_token: base_token.clone(),
                     lp_token,
@@ -104,6 +179,21 @@ common::config::ConfigModule
                     owner_fee,
                     liquidity_token: BigUint::zero(),
                     liquidity_base: BigUint::zero(),
+                    curve,
+                    amp: AmpRamp {
+                        initial_amp: amp,
+                        future_amp: amp,
+                        initial_time: now,
+                        future_time: now,
+                    },
+                    token_weight,
+                    base_weight,
+                    token_decimals: self.inspected_decimals(token).get(),
+                    max_price_impact: 0,
+                    max_swap_ratio: 0,
+                    token_risk: self.token_risk(token).get(),
+                    base_token_risk: self.token_risk(base_token).get(),
+                    base_decimals: self.inspected_decimals(base_token).get(),
                 };
                 self.last_pair_id().set(id + 1);
                 self.pair(id).set(pair);
@@ -155,7 +245,11 @@ common::config::ConfigModule
 
         let mut pair = self.pair(id).get();
         require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(!self.pair_state_forced(id).get(), ERROR_PAIR_STATE_FORCED);
+        require!(pair.state != PairState::Delisted, ERROR_PAIR_DELISTED);
+        require!(pair.state != PairState::PendingApproval, ERROR_PAIR_PENDING_APPROVAL);
         require!(pair.lp_supply > 0, ERROR_NO_LIQUIDITY);
+        self.check_token_transferable(&pair.token);
 
         pair.state = PairState::Active;
         self.pair(id).set(pair);
@@ -168,6 +262,9 @@ common::config::ConfigModule
 
         let mut pair = self.pair(id).get();
         require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(!self.pair_state_forced(id).get(), ERROR_PAIR_STATE_FORCED);
+        require!(pair.state != PairState::Delisted, ERROR_PAIR_DELISTED);
+        require!(pair.state != PairState::PendingApproval, ERROR_PAIR_PENDING_APPROVAL);
 
         pair.state = PairState::ActiveNoSwap;
         self.pair(id).set(pair);
@@ -180,11 +277,49 @@ common::config::ConfigModule
 
         let mut pair = self.pair(id).get();
         require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(!self.pair_state_forced(id).get(), ERROR_PAIR_STATE_FORCED);
+        require!(pair.state != PairState::Delisted, ERROR_PAIR_DELISTED);
+        require!(pair.state != PairState::PendingApproval, ERROR_PAIR_PENDING_APPROVAL);
 
         pair.state = PairState::Inactive;
         self.pair(id).set(pair);
     }
 
+    // retires the pair: only proportional liquidity withdrawals remain possible and the pair is removed after the last one
+    #[endpoint(setPairDelisted)]
+    fn set_pair_delisted(&self, id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let mut pair = self.pair(id).get();
+        require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(!self.pair_state_forced(id).get(), ERROR_PAIR_STATE_FORCED);
+        require!(pair.state != PairState::Delisted, ERROR_PAIR_DELISTED);
+
+        if pair.lp_supply == 0 {
+            self.clear_pair(id);
+        } else {
+            pair.state = PairState::Delisted;
+            self.pair(id).set(pair);
+        }
+    }
+
+    #[endpoint(removePair)]
+    fn remove_pair(&self, id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let pair = self.pair(id).get();
+        let caller = self.blockchain().get_caller();
+        if pair.owner != caller {
+            self.only_subscriber(&caller);
+        }
+        require!(pair.state == PairState::Delisted, ERROR_PAIR_NOT_DELISTED);
+        require!(pair.lp_supply == 0, ERROR_PAIR_HAS_LIQUIDITY);
+
+        self.clear_pair(id);
+    }
+
     #[endpoint(changePairFees)]
     fn change_pair_fees(
         &self,
@@ -197,12 +332,37 @@ common::config::ConfigModule
 
         let mut pair = self.pair(id).get();
         require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(new_lp_fee + new_owner_fee < MAX_PERCENT, ERROR_WRONG_FEES);
+        // the dynamic fee replaces the LP fee up to its maximum
+        if !self.dynamic_fee(id).is_empty() {
+            require!(self.dynamic_fee(id).get().max_fee + new_owner_fee < MAX_PERCENT, ERROR_WRONG_FEES);
+        }
+        self.check_fee_range(&pair.owner, new_lp_fee, new_owner_fee);
 
         pair.lp_fee = new_lp_fee;
         pair.owner_fee = new_owner_fee;
         self.pair(id).set(pair);
     }
 
+    #[endpoint(setPairSwapLimits)]
+    fn set_pair_swap_limits(
+        &self,
+        id: usize,
+        max_price_impact: u64,
+        max_swap_ratio: u64,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let mut pair = self.pair(id).get();
+        require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(max_price_impact <= MAX_PERCENT && max_swap_ratio <= MAX_PERCENT, ERROR_WRONG_SWAP_LIMITS);
+
+        pair.max_price_impact = max_price_impact;
+        pair.max_swap_ratio = max_swap_ratio;
+        self.pair(id).set(pair);
+    }
+
     #[endpoint(addBaseToken)]
     fn add_base_token(&self, token: TokenIdentifier) {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
@@ -229,19 +389,179 @@ common::config::ConfigModule
         self.base_tokens().swap_remove(&token);
     }
 
+    // moderation
+    #[endpoint(forcePairState)]
+    fn force_pair_state(&self, id: usize, state: PairState) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        // pending approval is only entered at creation, rejecting a pair forced there would remove its liquidity
+        require!(state != PairState::PendingApproval, ERROR_PAIR_PENDING_APPROVAL);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        if !self.pair(id).is_empty() {
+            let mut pair = self.pair(id).get();
+            if state == PairState::Active {
+                require!(pair.lp_supply > 0, ERROR_NO_LIQUIDITY);
+            }
+
+            pair.state = state;
+            self.pair(id).set(pair);
+        } else {
+            require!(!self.cl_pool(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+            let mut pool = self.cl_pool(id).get();
+            if state == PairState::Active {
+                require!(!self.cl_pool_ticks(id).is_empty(), ERROR_NO_LIQUIDITY);
+            }
+
+            pool.state = state;
+            self.cl_pool(id).set(pool);
+        }
+        self.pair_state_forced(id).set(true);
+    }
+
+    // gives the state control back to the pair owner
+    #[endpoint(releasePairState)]
+    fn release_pair_state(&self, id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.only_subscriber(&self.blockchain().get_caller());
+        require!(self.pair_state_forced(id).get(), ERROR_PAIR_STATE_NOT_FORCED);
+
+        self.pair_state_forced(id).clear();
+    }
+
+    #[endpoint(approvePair)]
+    fn approve_pair(&self, id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        if !self.pair(id).is_empty() {
+            let mut pair = self.pair(id).get();
+            require!(pair.state == PairState::PendingApproval, ERROR_PAIR_NOT_PENDING_APPROVAL);
+
+            pair.state = PairState::ActiveNoSwap;
+            self.pair(id).set(pair);
+        } else {
+            require!(!self.cl_pool(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+            let mut pool = self.cl_pool(id).get();
+            require!(pool.state == PairState::PendingApproval, ERROR_PAIR_NOT_PENDING_APPROVAL);
+
+            pool.state = PairState::ActiveNoSwap;
+            self.cl_pool(id).set(pool);
+        }
+    }
+
+    // pending pairs can't receive liquidity, so a rejected pair is simply removed
+    #[endpoint(rejectPair)]
+    fn reject_pair(&self, id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        let is_pair = !self.pair(id).is_empty();
+        if is_pair {
+            let pair = self.pair(id).get();
+            require!(pair.state == PairState::PendingApproval, ERROR_PAIR_NOT_PENDING_APPROVAL);
+            require!(pair.lp_supply == 0, ERROR_PAIR_HAS_LIQUIDITY);
+        } else {
+            require!(!self.cl_pool(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+            require!(self.cl_pool(id).get().state == PairState::PendingApproval, ERROR_PAIR_NOT_PENDING_APPROVAL);
+            require!(self.cl_pool_ticks(id).is_empty(), ERROR_PAIR_HAS_LIQUIDITY);
+        }
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        if is_pair {
+            self.clear_pair(id);
+        } else {
+            self.cl_pool(id).clear();
+            self.pair_state_forced(id).clear();
+        }
+    }
+
+    #[endpoint(delistPair)]
+    fn delist_pair(&self, id: usize) {
+        self.force_pair_state(id, PairState::Delisted);
+    }
+
+    #[endpoint(denyToken)]
+    fn deny_token(&self, token: TokenIdentifier) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.denied_tokens().contains(&token), ERROR_TOKEN_ALREADY_DENIED);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.denied_tokens().insert(token);
+    }
+
+    #[endpoint(allowToken)]
+    fn allow_token(&self, token: TokenIdentifier) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(self.denied_tokens().contains(&token), ERROR_TOKEN_NOT_DENIED);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.denied_tokens().swap_remove(&token);
+    }
+
     // helpers
-    fn check_whitelisted(&self, address: &ManagedAddress) {
-        self.platform_contract_proxy()
-            .contract(self.platform_sc().get())
-            .check_whitelisted(address)
-            .execute_on_dest_context::<()>();
-    }
-
-    fn only_subscriber(&self, address: &ManagedAddress) {
-        let id: Option<u64> = self.platform_contract_proxy()
-            .contract(self.platform_sc().get())
-            .get_subscriber_id_by_address(address)
-            .execute_on_dest_context();
-        require!(id.is_some(), ERROR_NOT_SUBSCRIBER);
+    // pairs listed before storage versioning become constant product pairs without limits, their tokens
+    // keep the default decimals and risk until inspected with inspectToken
+    fn migrate_legacy_pairs(&self) {
+        let now = self.blockchain().get_block_timestamp();
+        for id in 0..self.last_pair_id().get() {
+            if self.legacy_pair(id).is_empty() {
+                continue;
+            }
+
+            let legacy = self.legacy_pair(id).get();
+            self.pair(id).set(Pair {
+                id: legacy.id,
+                owner: legacy.owner,
+                state: legacy.state,
+                token: legacy.token,
+                base_token: legacy.base_token,
+                lp_token: legacy.lp_token,
+                lp_supply: legacy.lp_supply,
+                lp_fee: legacy.lp_fee,
+                owner_fee: legacy.owner_fee,
+                liquidity_token: legacy.liquidity_token,
+                liquidity_base: legacy.liquidity_base,
+                curve: Curve::ConstantProduct,
+                amp: AmpRamp {
+                    initial_amp: 0,
+                    future_amp: 0,
+                    initial_time: now,
+                    future_time: now,
+                },
+                token_weight: MAX_PERCENT / 2,
+                base_weight: MAX_PERCENT / 2,
+                token_decimals: LEGACY_TOKEN_DECIMALS,
+                max_price_impact: 0,
+                max_swap_ratio: 0,
+                token_risk: TokenRisk::default(),
+                base_token_risk: TokenRisk::default(),
+                base_decimals: LEGACY_TOKEN_DECIMALS,
+            });
+        }
+    }
+
+    fn rebuild_token_reserves(&self) {
+        self.token_reserves().clear();
+        for (token, amount) in self.referral_totals().iter() {
+            self.increase_token_reserve(&token, &amount);
+        }
+        for id in 0..self.last_pair_id().get() {
+            if !self.pair(id).is_empty() {
+                let pair = self.pair(id).get();
+                self.increase_token_reserve(&pair.token, &pair.liquidity_token);
+                self.increase_token_reserve(&pair.base_token, &pair.liquidity_base);
+            }
+            if !self.cl_pool(id).is_empty() {
+                let pool = self.cl_pool(id).get();
+                self.increase_token_reserve(&pool.token, &pool.reserve_token);
+                self.increase_token_reserve(&pool.base_token, &pool.reserve_base);
+            }
+            if !self.farm(id).is_empty() {
+                let farm = self.farm(id).get();
+                self.increase_token_reserve(&farm.lp_token, &farm.total_staked);
+                self.increase_token_reserve(&farm.reward_token, &(&farm.reward_reserve + &farm.rewards_unclaimed));
+                self.increase_token_reserve(&farm.lp_token, &self.get_lock_pool(id).total_locked);
+            }
+        }
     }
 }
@@ -1,6 +1,7 @@
 use tfn_dex::common::errors::*;
 
 use crate::common::{self, config::*, errors::*};
+use tfn_test_dex_core::{liquidity::*, stable_swap::get_precision_rate};
 
 multiversx_sc::imports!();
 
@@ -8,13 +9,21 @@ multiversx_sc::imports!();
 pub trait LiquidityModule:
 common::config::ConfigModule
 +super::helpers::HelpersModule
++super::stable_swap::StableSwapModule
++super::observations::ObservationsModule
++super::reserves::ReservesModule
++super::dynamic_fee::DynamicFeeModule
++super::circuit_breaker::CircuitBreakerModule
++super::wegld::WegldModule
++super::history::HistoryModule
++super::pair_policy::PairPolicyModule
 {
     #[endpoint(addLiquidity)]
     #[payable("*")]
     fn add_liquidity(&self) {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
 
-        let payments = self.call_value().all_esdt_transfers();
+        let (payments, paid_egld) = self.get_wrapped_payments();
         require!(payments.len() == 2, ERROR_WRONG_PAYMENT);
 
         let mut pair = match self.get_pair_by_tickers(&payments.get(0).token_identifier, &payments.get(1).token_identifier) {
@@ -22,79 +31,121 @@ common::config::ConfigModule
             Option::None => sc_panic!(ERROR_PAIR_NOT_FOUND),
         };
         require!(pair.state != PairState::Inactive, ERROR_PAIR_NOT_ACTIVE);
+        require!(pair.state != PairState::Delisted, ERROR_PAIR_DELISTED);
+        require!(pair.state != PairState::PendingApproval, ERROR_PAIR_PENDING_APPROVAL);
 
+        let caller = self.blockchain().get_caller();
+        let token_index = if payments.get(0).token_identifier == pair.token { 0 } else { 1 };
+        let token_amount = payments.get(token_index).amount.clone();
+        let base_amount = payments.get(1 - token_index).amount.clone();
         if pair.lp_supply == 0 {
-            require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+            require!(pair.owner == caller, ERROR_NOT_PAIR_OWNER);
+            require!(base_amount >= self.min_initial_liquidity(&pair.base_token).get(), ERROR_INITIAL_LIQUIDITY_TOO_LOW);
         }
-
-        let caller = self.blockchain().get_caller();
-        let (mut base_amount, mut token_amount) = if payments.get(0).token_identifier == pair.token {
-            (payments.get(1).amount, payments.get(0).amount)
-        } else {
-            (payments.get(0).amount, payments.get(1).amount)
-        };
-        let lp_token_amount = if pair.lp_supply == BigUint::zero() {
-            base_amount.clone()
-        } else {
-            let base_optimal = self.quote(&token_amount, &pair.liquidity_token, &pair.liquidity_base);
-            let (token_added, base_added) = if base_optimal < base_amount {
-                (token_amount.clone(), base_optimal)
-            } else {
-                let token_optimal = self.quote(&base_amount, &pair.liquidity_base, &pair.liquidity_token);
-
-                (token_optimal, base_amount.clone())
-            };
-            // return surplus tokens
-            if token_added < token_amount {
-                self.send().direct_esdt(&caller, &pair.token, 0, &(&token_amount - &token_added));
-                token_amount = token_added;
-            }
-            if base_added < base_amount {
-                self.send().direct_esdt(&caller, &pair.base_token, 0, &(&base_amount - &base_added));
-                base_amount = base_added;
-            }
-
-            let first_potential_lp = &token_amount * &pair.lp_supply / &pair.liquidity_token;
-            let second_potential_lp = &base_amount * &pair.lp_supply / &pair.liquidity_base;
-
-            core::cmp::min(first_potential_lp, second_potential_lp)
-        };
-        pair.liquidity_base += &base_amount;
-        pair.liquidity_token += &token_amount;
-        pair.lp_supply += &lp_token_amount;
-        self.pair(pair.id).set(&pair);
-
+        let (lp_token_amount, token_added, base_added) = self.add_liquidity_internal(&mut pair, &token_amount, &base_amount);
         self.send().esdt_local_mint(&pair.lp_token, 0, &lp_token_amount);
-        self.send().direct_esdt(&caller, &pair.lp_token, 0, &lp_token_amount);
+
+        // return surplus tokens along with the LP tokens
+        let mut outputs = ManagedVec::new();
+        let mut egld = BigUint::zero();
+        self.add_output(&mut outputs, &mut egld, EsdtTokenPayment::new(pair.token.clone(), 0, token_amount - token_added), paid_egld.get(token_index));
+        self.add_output(&mut outputs, &mut egld, EsdtTokenPayment::new(pair.base_token.clone(), 0, base_amount - base_added), paid_egld.get(1 - token_index));
+        self.add_output(&mut outputs, &mut egld, EsdtTokenPayment::new(pair.lp_token, 0, lp_token_amount), false);
+        self.send_outputs(&caller, &outputs, &egld);
     }
 
     #[endpoint(removeLiquidity)]
     #[payable("*")]
-    fn remove_liquidity(&self) {
-        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+    fn remove_liquidity(&self, unwrap_egld: OptionalValue<bool>) {
+        let state = self.state().get();
+        require!(state == State::Active || state == State::Emergency, ERROR_NOT_ACTIVE);
 
         let payment = self.call_value().single_esdt();
         let mut pair = match self.get_pair_by_lp_token(&payment.token_identifier) {
             Option::Some(pair) => pair,
             Option::None => sc_panic!(ERROR_WRONG_PAYMENT),
         };
-        require!(pair.state != PairState::Inactive, ERROR_PAIR_NOT_ACTIVE);
+        if state == State::Active {
+            require!(pair.state != PairState::Inactive, ERROR_PAIR_NOT_ACTIVE);
+        }
 
         let caller = self.blockchain().get_caller();
         let lp_token_amount = payment.amount;
-        let base_amount = &pair.liquidity_base * &lp_token_amount / &pair.lp_supply;
-        let token_amount = &pair.liquidity_token * &lp_token_amount / &pair.lp_supply;
+        let (token_amount, base_amount) =
+            get_removed_amounts(&lp_token_amount, &pair.liquidity_token, &pair.liquidity_base, &pair.lp_supply);
 
         pair.liquidity_base -= &base_amount;
         pair.liquidity_token -= &token_amount;
         pair.lp_supply -= &lp_token_amount;
-        if pair.lp_supply == 0 {
+        if pair.lp_supply == 0 && pair.state != PairState::Delisted {
             pair.state = PairState::ActiveNoSwap;
         }
-        self.pair(pair.id).set(&pair);
+        self.update_pair(&pair);
+        self.record_trade(pair.id, TradeKind::RemoveLiquidity, &token_amount, &base_amount);
+        if pair.lp_supply == 0 && pair.state == PairState::Delisted {
+            self.clear_pair(pair.id);
+        } else {
+            self.update_price_observation(&pair);
+        }
 
         self.send().esdt_local_burn(&pair.lp_token, 0, &lp_token_amount);
-        self.send().direct_esdt(&caller, &pair.base_token, 0, &base_amount);
-        self.send().direct_esdt(&caller, &pair.token, 0, &token_amount);
+        let unwrap_egld = unwrap_egld.into_option().unwrap_or_default();
+        let mut outputs = ManagedVec::new();
+        let mut egld = BigUint::zero();
+        self.add_output(&mut outputs, &mut egld, EsdtTokenPayment::new(pair.base_token, 0, base_amount), unwrap_egld);
+        self.add_output(&mut outputs, &mut egld, EsdtTokenPayment::new(pair.token, 0, token_amount), unwrap_egld);
+        self.send_outputs(&caller, &outputs, &egld);
+    }
+
+    // helpers
+    // adds as much of the amounts as the pair's ratio allows, returning the LP tokens to be minted and the amounts used
+    fn add_liquidity_internal(
+        &self,
+        pair: &mut Pair<Self::Api>,
+        token_amount: &BigUint,
+        base_amount: &BigUint,
+    ) -> (BigUint, BigUint, BigUint) {
+        let (token_added, base_added, lp_token_amount) = if pair.lp_supply == BigUint::zero() {
+            let lp_token_amount = get_initial_lp_amount(
+                token_amount,
+                base_amount,
+                pair.curve.into(),
+                self.get_current_amp(&pair.amp),
+                pair.base_weight,
+                get_precision_rate(pair.token_decimals),
+                get_precision_rate(pair.base_decimals),
+            );
+
+            (token_amount.clone(), base_amount.clone(), lp_token_amount)
+        } else {
+            let (token_added, base_added) =
+                get_optimal_amounts(token_amount, base_amount, &pair.liquidity_token, &pair.liquidity_base);
+            let lp_token_amount =
+                get_lp_amount(&token_added, &base_added, &pair.liquidity_token, &pair.liquidity_base, &pair.lp_supply);
+
+            (token_added, base_added, lp_token_amount)
+        };
+        pair.liquidity_base += &base_added;
+        pair.liquidity_token += &token_added;
+        pair.lp_supply += &lp_token_amount;
+        self.update_pair(pair);
+        self.update_price_observation(pair);
+        self.record_trade(pair.id, TradeKind::AddLiquidity, &token_added, &base_added);
+
+        (lp_token_amount, token_added, base_added)
+    }
+
+    // removes a retired pair along with everything stored for it, so that it can be created again
+    fn clear_pair(&self, id: usize) {
+        self.pair(id).clear();
+        self.pair_state_forced(id).clear();
+        self.price_observation(id).clear();
+        self.swap_observation_count(id).clear();
+        self.dynamic_fee(id).clear();
+        self.fee_window_previous(id).clear();
+        self.fee_window_start(id).clear();
+        self.circuit_breaker(id).clear();
+        self.circuit_breaker_reference(id).clear();
+        self.circuit_breaker_trip(id).clear();
     }
 }
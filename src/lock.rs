@@ -0,0 +1,261 @@
+use tfn_dex::common::{consts::*, errors::*};
+
+use crate::common::{self, config::*, consts::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait LockModule:
+common::config::ConfigModule
++super::helpers::HelpersModule
++super::reserves::ReservesModule
++super::farm::FarmModule
+{
+    #[only_owner]
+    #[payable("EGLD")]
+    #[endpoint(issueLockToken)]
+    fn issue_lock_token(&self) {
+        require!(self.lock_token().is_empty(), ERROR_LOCK_TOKEN_ALREADY_ISSUED);
+
+        let caller = self.blockchain().get_caller();
+        let issue_cost = self.call_value().egld_value().clone_value();
+        self.send()
+            .esdt_system_sc_proxy()
+            .issue_and_set_all_roles(
+                issue_cost,
+                ManagedBuffer::from(LOCK_TOKEN_NAME),
+                ManagedBuffer::from(LOCK_TOKEN_TICKER),
+                EsdtTokenType::NonFungible,
+                0,
+            )
+            .with_callback(self.callbacks().lock_token_issue_callback(caller))
+            .async_call_and_exit();
+    }
+
+    #[callback]
+    fn lock_token_issue_callback(
+        &self,
+        caller: ManagedAddress,
+        #[call_result] result: ManagedAsyncCallResult<TokenIdentifier>,
+    ) {
+        match result {
+            ManagedAsyncCallResult::Ok(lock_token) => {
+                self.lock_token().set(lock_token);
+            }
+            ManagedAsyncCallResult::Err(_) => {
+                let issue_cost = self.call_value().egld_value();
+                self.send().direct_egld(&caller, &issue_cost);
+            }
+        }
+    }
+
+    // only the SC holds the transfer role, so lock positions can be sent back to it but not to other users
+    #[only_owner]
+    #[endpoint(setLockTokenTransferRole)]
+    fn set_lock_token_transfer_role(&self) {
+        require!(!self.lock_token().is_empty(), ERROR_LOCK_TOKEN_NOT_ISSUED);
+
+        self.send()
+            .esdt_system_sc_proxy()
+            .set_special_roles(
+                &self.blockchain().get_sc_address(),
+                &self.lock_token().get(),
+                [EsdtLocalRole::Transfer].iter().cloned(),
+            )
+            .with_callback(self.callbacks().lock_token_transfer_role_callback())
+            .async_call_and_exit();
+    }
+
+    #[callback]
+    fn lock_token_transfer_role_callback(&self, #[call_result] result: ManagedAsyncCallResult<()>) {
+        if let ManagedAsyncCallResult::Ok(()) = result {
+            self.lock_token_transfer_role_set().set(true);
+        }
+    }
+
+    #[payable("*")]
+    #[endpoint(lockLp)]
+    fn lock_lp(&self, lock_period: u64) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.lock_token().is_empty(), ERROR_LOCK_TOKEN_NOT_ISSUED);
+        // without the role, positions could be traded between users
+        require!(self.lock_token_transfer_role_set().get(), ERROR_LOCK_TOKEN_TRANSFER_ROLE_NOT_SET);
+
+        let payment = self.call_value().single_esdt();
+        let pair = match self.get_pair_by_lp_token(&payment.token_identifier) {
+            Option::Some(pair) => pair,
+            Option::None => sc_panic!(ERROR_WRONG_PAYMENT),
+        };
+        require!(pair.state != PairState::Delisted, ERROR_PAIR_DELISTED);
+        require!(payment.amount > 0, ERROR_ZERO_AMOUNT);
+        require!((MIN_LOCK_PERIOD..=MAX_LOCK_PERIOD).contains(&lock_period), ERROR_WRONG_LOCK_PERIOD);
+
+        let weight = &payment.amount * self.get_lock_boost(lock_period) / MAX_PERCENT;
+        let mut farm = self.get_or_create_farm(pair.id);
+        farm.total_weight += &weight;
+        let mut lock_pool = self.get_lock_pool(pair.id);
+        lock_pool.total_locked += &payment.amount;
+        lock_pool.total_weight += &weight;
+        self.increase_token_reserve(&pair.lp_token, &payment.amount);
+
+        let now = self.blockchain().get_block_timestamp();
+        let position = LockPosition {
+            pair_id: pair.id,
+            amount: payment.amount,
+            weight,
+            lock_time: now,
+            unlock_time: now + lock_period,
+            reward_per_share: farm.reward_per_share.clone(),
+            penalty_per_weight: lock_pool.penalty_per_weight.clone(),
+        };
+        self.farm(pair.id).set(farm);
+        self.lock_pool(pair.id).set(lock_pool);
+
+        let lock_token = self.lock_token().get();
+        let amount = BigUint::from(1u64);
+        let nonce = self.send().esdt_nft_create_compact(&lock_token, &amount, &position);
+        self.lock_position(nonce).set(position);
+        self.send().direct_esdt(&self.blockchain().get_caller(), &lock_token, nonce, &amount);
+    }
+
+    // unlocking before the unlock time costs a penalty proportional to the time left, shared by the other lockers;
+    // there is no penalty in emergency mode or if the pair was delisted
+    #[payable("*")]
+    #[endpoint(unlockLp)]
+    fn unlock_lp(&self) {
+        let state = self.state().get();
+        require!(state == State::Active || state == State::Emergency, ERROR_NOT_ACTIVE);
+
+        let payment = self.call_value().single_esdt();
+        let mut position = self.get_paid_lock_position(&payment);
+        let mut pair = self.pair(position.pair_id).get();
+        let mut farm = self.get_updated_farm(position.pair_id);
+        let rewards = self.take_lock_rewards(&mut farm, &mut position);
+        farm.total_weight -= &position.weight;
+        self.farm(position.pair_id).set(&farm);
+
+        let mut lock_pool = self.get_lock_pool(position.pair_id);
+        let penalty_share = &position.weight * &(&lock_pool.penalty_per_weight - &position.penalty_per_weight) / WAD;
+        lock_pool.total_weight -= &position.weight;
+        let now = self.blockchain().get_block_timestamp();
+        let penalty = if now < position.unlock_time && state == State::Active && pair.state != PairState::Delisted {
+            &position.amount * MAX_EARLY_UNLOCK_PENALTY * (position.unlock_time - now)
+                / (MAX_PERCENT * (position.unlock_time - position.lock_time))
+        } else {
+            BigUint::zero()
+        };
+        let amount = &position.amount + &penalty_share - &penalty;
+        lock_pool.total_locked -= &amount;
+        self.decrease_token_reserve(&pair.lp_token, &amount);
+        if penalty > 0 {
+            if lock_pool.total_weight > 0 {
+                lock_pool.penalty_per_weight += &penalty * WAD / &lock_pool.total_weight;
+            } else {
+                // nobody left to share it with, so burning it benefits all the pair's liquidity providers
+                lock_pool.total_locked -= &penalty;
+                self.decrease_token_reserve(&pair.lp_token, &penalty);
+                self.send().esdt_local_burn(&pair.lp_token, 0, &penalty);
+                pair.lp_supply -= &penalty;
+                self.update_pair(&pair);
+            }
+        }
+        self.lock_pool(position.pair_id).set(lock_pool);
+        self.lock_position(payment.token_nonce).clear();
+
+        let caller = self.blockchain().get_caller();
+        self.send().esdt_local_burn(&payment.token_identifier, payment.token_nonce, &payment.amount);
+        self.send().direct_esdt(&caller, &pair.lp_token, 0, &amount);
+        if rewards > 0 {
+            self.send().direct_esdt(&caller, &farm.reward_token, 0, &rewards);
+        }
+    }
+
+    #[payable("*")]
+    #[endpoint(claimLockRewards)]
+    fn claim_lock_rewards(&self) {
+        let state = self.state().get();
+        require!(state == State::Active || state == State::Emergency, ERROR_NOT_ACTIVE);
+
+        let payment = self.call_value().single_esdt();
+        let mut position = self.get_paid_lock_position(&payment);
+        let mut farm = self.get_updated_farm(position.pair_id);
+        let rewards = self.take_lock_rewards(&mut farm, &mut position);
+        require!(rewards > 0, ERROR_NO_REWARDS);
+
+        self.farm(position.pair_id).set(&farm);
+        self.lock_position(payment.token_nonce).set(position);
+
+        let caller = self.blockchain().get_caller();
+        self.send().direct_esdt(&caller, &payment.token_identifier, payment.token_nonce, &payment.amount);
+        self.send().direct_esdt(&caller, &farm.reward_token, 0, &rewards);
+    }
+
+    // views
+    #[view(getLockBoost)]
+    fn get_lock_boost(&self, lock_period: u64) -> u64 {
+        MAX_PERCENT + MAX_LOCK_BOOST * lock_period / MAX_LOCK_PERIOD
+    }
+
+    #[view(getLockPool)]
+    fn get_lock_pool(&self, pair_id: usize) -> LockPool<Self::Api> {
+        if self.lock_pool(pair_id).is_empty() {
+            return LockPool {
+                total_locked: BigUint::zero(),
+                total_weight: BigUint::zero(),
+                penalty_per_weight: BigUint::zero(),
+            };
+        }
+
+        self.lock_pool(pair_id).get()
+    }
+
+    // the farm rewards and the share of early unlock penalties currently earned by the position
+    #[view(getLockRewards)]
+    fn get_lock_rewards(&self, nonce: u64) -> MultiValue2<BigUint, BigUint> {
+        require!(!self.lock_position(nonce).is_empty(), ERROR_LOCK_NOT_FOUND);
+
+        let position = self.lock_position(nonce).get();
+        let farm = self.get_updated_farm(position.pair_id);
+        let lock_pool = self.get_lock_pool(position.pair_id);
+        let rewards = &position.weight * &(&farm.reward_per_share - &position.reward_per_share) / WAD;
+        let penalty_share = &position.weight * &(&lock_pool.penalty_per_weight - &position.penalty_per_weight) / WAD;
+
+        (rewards, penalty_share).into()
+    }
+
+    // helpers
+    fn get_paid_lock_position(&self, payment: &EsdtTokenPayment) -> LockPosition<Self::Api> {
+        require!(
+            !self.lock_token().is_empty() && payment.token_identifier == self.lock_token().get(),
+            ERROR_WRONG_PAYMENT
+        );
+        require!(!self.lock_position(payment.token_nonce).is_empty(), ERROR_LOCK_NOT_FOUND);
+
+        self.lock_position(payment.token_nonce).get()
+    }
+
+    fn take_lock_rewards(&self, farm: &mut Farm<Self::Api>, position: &mut LockPosition<Self::Api>) -> BigUint {
+        let rewards = &position.weight * &(&farm.reward_per_share - &position.reward_per_share) / WAD;
+        position.reward_per_share = farm.reward_per_share.clone();
+        farm.rewards_unclaimed -= &rewards;
+        self.decrease_token_reserve(&farm.reward_token, &rewards);
+
+        rewards
+    }
+
+    // storage
+    #[view(getLockToken)]
+    #[storage_mapper("lock_token")]
+    fn lock_token(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[view(getLockTokenTransferRoleSet)]
+    #[storage_mapper("lock_token_transfer_role_set")]
+    fn lock_token_transfer_role_set(&self) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("lock_pools")]
+    fn lock_pool(&self, pair_id: usize) -> SingleValueMapper<LockPool<Self::Api>>;
+
+    #[view(getLockPosition)]
+    #[storage_mapper("lock_positions")]
+    fn lock_position(&self, nonce: u64) -> SingleValueMapper<LockPosition<Self::Api>>;
+}
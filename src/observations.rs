@@ -0,0 +1,180 @@
+use crate::common::{self, config::*, consts::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait ObservationsModule:
+common::config::ConfigModule
++super::helpers::HelpersModule
+{
+    // should be called after every change of the pair's reserves
+    fn update_price_observation(&self, pair: &Pair<Self::Api>) {
+        let price = if pair.liquidity_token > 0 && pair.liquidity_base > 0 {
+            self.get_spot_price(pair)
+        } else {
+            BigUint::zero()
+        };
+        let mut observation = self.get_current_price_observation(pair.id);
+        observation.price = price;
+        self.price_observation(pair.id).set(observation);
+    }
+
+    // the last observation, with the cumulative price brought up to the current block
+    fn get_current_price_observation(&self, pair_id: usize) -> PriceObservation<Self::Api> {
+        let now = self.blockchain().get_block_timestamp();
+        if self.price_observation(pair_id).is_empty() {
+            return PriceObservation {
+                timestamp: now,
+                price: BigUint::zero(),
+                price_cumulative: BigUint::zero(),
+            };
+        }
+
+        let last = self.price_observation(pair_id).get();
+        PriceObservation {
+            timestamp: now,
+            price_cumulative: &last.price_cumulative + &(&last.price * (now - last.timestamp)),
+            price: last.price,
+        }
+    }
+
+    fn get_twap_since(&self, pair_id: usize, since: &PriceObservation<Self::Api>) -> BigUint {
+        let current = self.get_current_price_observation(pair_id);
+        if current.timestamp <= since.timestamp {
+            return current.price;
+        }
+
+        (current.price_cumulative - &since.price_cumulative) / (current.timestamp - since.timestamp)
+    }
+
+    // keeps the last MAX_SWAP_OBSERVATIONS blocks with swaps, adding up the volume of swaps in the same block
+    fn record_swap_observation(&self, pair: &Pair<Self::Api>, base_volume: &BigUint) {
+        let block = self.blockchain().get_block_nonce();
+        let count = self.swap_observation_count(pair.id).get();
+        let mut volume = base_volume.clone();
+        let mut index = count;
+        if count > 0 {
+            let last = self.swap_observation(pair.id, (count - 1) % MAX_SWAP_OBSERVATIONS).get();
+            if last.block == block {
+                volume += last.volume;
+                index = count - 1;
+            }
+        }
+
+        self.swap_observation(pair.id, index % MAX_SWAP_OBSERVATIONS).set(SwapObservation {
+            timestamp: self.blockchain().get_block_timestamp(),
+            block,
+            liquidity_token: pair.liquidity_token.clone(),
+            liquidity_base: pair.liquidity_base.clone(),
+            price: self.get_spot_price(pair),
+            volume,
+        });
+        self.swap_observation_count(pair.id).set(index + 1);
+    }
+
+    // the last `count` candles of `interval` seconds, aligned to multiples of the interval and ending with the current one;
+    // each candle opens at the previous close, and candles before the first stored observation are zero
+    #[view(getOhlc)]
+    fn get_ohlc(&self, pair_id: usize, interval: u64, count: u64) -> ManagedVec<Candle<Self::Api>> {
+        require!(interval > 0 && count > 0 && count <= MAX_OHLC_CANDLES, ERROR_WRONG_OHLC_PARAMS);
+
+        let end = self.blockchain().get_block_timestamp() / interval + 1;
+        let first = end.saturating_sub(count);
+        let start = first * interval;
+
+        // find the oldest stored observation inside the requested period
+        let total = self.swap_observation_count(pair_id).get();
+        let stored = core::cmp::min(total, MAX_SWAP_OBSERVATIONS);
+        let mut inside = 0;
+        let mut last_price = BigUint::zero();
+        while inside < stored {
+            let observation = self.swap_observation(pair_id, (total - 1 - inside) % MAX_SWAP_OBSERVATIONS).get();
+            if observation.timestamp < start {
+                last_price = observation.price;
+                break;
+            }
+
+            inside += 1;
+        }
+
+        let mut candles = ManagedVec::new();
+        let mut index = total - inside;
+        for bucket in first..end {
+            let mut candle = Candle {
+                open_time: bucket * interval,
+                open: last_price.clone(),
+                high: last_price.clone(),
+                low: last_price.clone(),
+                close: last_price.clone(),
+                volume: BigUint::zero(),
+            };
+            while index < total {
+                let observation = self.swap_observation(pair_id, index % MAX_SWAP_OBSERVATIONS).get();
+                if observation.timestamp >= candle.open_time + interval {
+                    break;
+                }
+
+                if candle.close == 0 {
+                    candle.open = observation.price.clone();
+                    candle.high = observation.price.clone();
+                    candle.low = observation.price.clone();
+                }
+                if observation.price > candle.high {
+                    candle.high = observation.price.clone();
+                }
+                if observation.price < candle.low {
+                    candle.low = observation.price.clone();
+                }
+                candle.close = observation.price;
+                candle.volume += observation.volume;
+                index += 1;
+            }
+            last_price = candle.close.clone();
+            candles.push(candle);
+        }
+
+        candles
+    }
+
+    // None if the stored history does not cover the whole period
+    fn get_pair_twap(&self, pair_id: usize, period: u64) -> Option<BigUint> {
+        let now = self.blockchain().get_block_timestamp();
+        let start = now.saturating_sub(period);
+        let last = self.price_observation(pair_id).get();
+        if last.timestamp <= start {
+            return Some(last.price);
+        }
+
+        // going back in time, each swap observation's price lasted until the next one, the last price until now
+        let total = self.swap_observation_count(pair_id).get();
+        let stored = core::cmp::min(total, MAX_SWAP_OBSERVATIONS);
+        let mut weighted = BigUint::zero();
+        let mut end = now;
+        for age in 0..stored {
+            let observation = self.swap_observation(pair_id, (total - 1 - age) % MAX_SWAP_OBSERVATIONS).get();
+            let price = if age == 0 { &last.price } else { &observation.price };
+            let segment_start = core::cmp::max(observation.timestamp, start);
+            weighted += price * (end - segment_start);
+            if observation.timestamp <= start {
+                return Some(weighted / period);
+            }
+
+            end = observation.timestamp;
+        }
+
+        None
+    }
+
+    #[view(getPriceObservation)]
+    #[storage_mapper("price_observations")]
+    fn price_observation(&self, pair_id: usize) -> SingleValueMapper<PriceObservation<Self::Api>>;
+
+    #[view(getSwapObservationCount)]
+    #[storage_mapper("swap_observation_count")]
+    fn swap_observation_count(&self, pair_id: usize) -> SingleValueMapper<u64>;
+
+    // the latest observation is at index (count - 1) % MAX_SWAP_OBSERVATIONS
+    #[view(getSwapObservation)]
+    #[storage_mapper("swap_observations")]
+    fn swap_observation(&self, pair_id: usize, index: u64) -> SingleValueMapper<SwapObservation<Self::Api>>;
+}
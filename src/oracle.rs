@@ -0,0 +1,163 @@
+use tfn_dex::common::errors::*;
+
+use crate::common::{self, config::*, consts::*, errors::*};
+
+multiversx_sc::imports!();
+
+// prices are in base token units for one token unit, scaled by 10^18, so that amount * price / 10^18 values an amount
+#[multiversx_sc::module]
+pub trait OracleModule:
+common::config::ConfigModule
++super::helpers::HelpersModule
++super::observations::ObservationsModule
+{
+    // pairs holding less than `min_liquidity` of the token, on either side, are not used as price sources
+    #[endpoint(setOracleMinLiquidity)]
+    fn set_oracle_min_liquidity(&self, token: TokenIdentifier, min_liquidity: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.oracle_min_liquidity(&token).set(min_liquidity);
+    }
+
+    // views
+    #[view(getOracleSpotPrice)]
+    fn get_oracle_spot_price(&self, token: TokenIdentifier, base_token: TokenIdentifier, max_staleness: u64) -> BigUint {
+        let route = self.get_oracle_route(&token, &base_token);
+        for pair in route.iter() {
+            self.check_oracle_staleness(&pair, max_staleness);
+        }
+
+        self.get_route_spot_price(&token, &route)
+    }
+
+    // the time-weighted average of each pair's price over the last `period` seconds
+    #[view(getOracleTwap)]
+    fn get_oracle_twap(
+        &self,
+        token: TokenIdentifier,
+        base_token: TokenIdentifier,
+        period: u64,
+        max_staleness: u64,
+    ) -> BigUint {
+        require!(period > 0, ERROR_WRONG_ORACLE_PERIOD);
+
+        let mut price = BigUint::from(WAD);
+        let mut from = token;
+        for pair in self.get_oracle_route(&from, &base_token).iter() {
+            self.check_oracle_staleness(&pair, max_staleness);
+            let twap = match self.get_pair_twap(pair.id, period) {
+                Some(twap) => twap,
+                None => sc_panic!(ERROR_ORACLE_HISTORY_TOO_SHORT),
+            };
+            require!(twap > 0, ERROR_ORACLE_HISTORY_TOO_SHORT);
+
+            price = price * self.get_oriented_price(&pair, &from, twap) / WAD;
+            from = self.get_other_token(&pair, &from);
+        }
+
+        price
+    }
+
+    #[view(getOracleRoute)]
+    fn get_oracle_route_view(&self, token: TokenIdentifier, base_token: TokenIdentifier) -> ManagedVec<Pair<Self::Api>> {
+        self.get_oracle_route(&token, &base_token)
+    }
+
+    // helpers
+    fn get_oracle_route(&self, token: &TokenIdentifier, base_token: &TokenIdentifier) -> ManagedVec<Pair<Self::Api>> {
+        match self.find_oracle_route(token, base_token) {
+            Some(route) => route,
+            None => sc_panic!(ERROR_NO_ORACLE_ROUTE),
+        }
+    }
+
+    // the pairs used to price `token` in `base_token`: the direct pair or, if there is none, two pairs sharing a token
+    fn find_oracle_route(&self, token: &TokenIdentifier, base_token: &TokenIdentifier) -> Option<ManagedVec<Pair<Self::Api>>> {
+        let mut route = ManagedVec::new();
+        if token == base_token {
+            return Some(route);
+        }
+
+        if let Some(pair) = self.get_oracle_pair(token, base_token) {
+            route.push(pair);
+            return Some(route);
+        }
+
+        for id in 0..self.last_pair_id().get() {
+            if self.pair(id).is_empty() {
+                continue;
+            }
+
+            let pair = self.pair(id).get();
+            if &pair.token != token && &pair.base_token != token {
+                continue;
+            }
+
+            let middle = self.get_other_token(&pair, token);
+            if let (Some(first), Some(second)) = (self.get_oracle_pair(token, &middle), self.get_oracle_pair(&middle, base_token)) {
+                route.push(first);
+                route.push(second);
+                return Some(route);
+            }
+        }
+
+        None
+    }
+
+    fn get_route_spot_price(&self, token: &TokenIdentifier, route: &ManagedVec<Pair<Self::Api>>) -> BigUint {
+        let mut price = BigUint::from(WAD);
+        let mut from = token.clone();
+        for pair in route.iter() {
+            price = price * self.get_oriented_price(&pair, &from, self.get_spot_price(&pair)) / WAD;
+            from = self.get_other_token(&pair, &from);
+        }
+
+        price
+    }
+
+    fn get_oracle_pair(&self, token1: &TokenIdentifier, token2: &TokenIdentifier) -> Option<Pair<Self::Api>> {
+        let pair = self.get_pair_by_tickers(token1, token2)?;
+        if pair.state != PairState::Active
+            || pair.liquidity_token == 0
+            || pair.liquidity_base == 0
+            || pair.liquidity_token < self.oracle_min_liquidity(&pair.token).get()
+            || pair.liquidity_base < self.oracle_min_liquidity(&pair.base_token).get()
+        {
+            return None;
+        }
+
+        Some(pair)
+    }
+
+    // a `max_staleness` of 0 accepts prices however long ago the pair's reserves last changed
+    fn check_oracle_staleness(&self, pair: &Pair<Self::Api>, max_staleness: u64) {
+        if max_staleness == 0 {
+            return;
+        }
+
+        let last_update = self.price_observation(pair.id).get().timestamp;
+        require!(self.blockchain().get_block_timestamp() - last_update <= max_staleness, ERROR_ORACLE_PRICE_STALE);
+    }
+
+    fn get_oriented_price(&self, pair: &Pair<Self::Api>, from: &TokenIdentifier, price: BigUint) -> BigUint {
+        if &pair.token == from {
+            price
+        } else {
+            BigUint::from(WAD) * WAD / price
+        }
+    }
+
+    fn get_other_token(&self, pair: &Pair<Self::Api>, token: &TokenIdentifier) -> TokenIdentifier {
+        if &pair.token == token {
+            pair.base_token.clone()
+        } else {
+            pair.token.clone()
+        }
+    }
+
+    // storage
+    #[view(getOracleMinLiquidity)]
+    #[storage_mapper("oracle_min_liquidity")]
+    fn oracle_min_liquidity(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+}
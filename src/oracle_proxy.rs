@@ -0,0 +1,221 @@
+// Code generated by the multiversx-sc proxy generator. DO NOT EDIT.
+
+////////////////////////////////////////////////////
+////////////////// AUTO-GENERATED //////////////////
+////////////////////////////////////////////////////
+
+#![allow(dead_code)]
+#![allow(clippy::all)]
+
+use multiversx_sc::proxy_imports::*;
+
+pub struct TfnTestDexProxy;
+
+impl<Env, From, To, Gas> TxProxyTrait<Env, From, To, Gas> for TfnTestDexProxy
+where
+    Env: TxEnv,
+    From: TxFrom<Env>,
+    To: TxTo<Env>,
+    Gas: TxGas<Env>,
+{
+    type TxProxyMethods = TfnTestDexProxyMethods<Env, From, To, Gas>;
+
+    fn proxy_methods(self, tx: Tx<Env, From, To, (), Gas, (), ()>) -> Self::TxProxyMethods {
+        TfnTestDexProxyMethods { wrapped_tx: tx }
+    }
+}
+
+pub struct TfnTestDexProxyMethods<Env, From, To, Gas>
+where
+    Env: TxEnv,
+    From: TxFrom<Env>,
+    To: TxTo<Env>,
+    Gas: TxGas<Env>,
+{
+    wrapped_tx: Tx<Env, From, To, (), Gas, (), ()>,
+}
+
+#[rustfmt::skip]
+impl<Env, From, Gas> TfnTestDexProxyMethods<Env, From, (), Gas>
+where
+    Env: TxEnv,
+    Env::Api: VMApi,
+    From: TxFrom<Env>,
+    Gas: TxGas<Env>,
+{
+    pub fn init(
+        self,
+    ) -> TxTypedDeploy<Env, From, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_deploy()
+            .original_result()
+    }
+}
+
+#[rustfmt::skip]
+impl<Env, From, To, Gas> TfnTestDexProxyMethods<Env, From, To, Gas>
+where
+    Env: TxEnv,
+    Env::Api: VMApi,
+    From: TxFrom<Env>,
+    To: TxTo<Env>,
+    Gas: TxGas<Env>,
+{
+    pub fn upgrade(
+        self,
+    ) -> TxTypedUpgrade<Env, From, To, NotPayable, Gas, ()> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_upgrade()
+            .original_result()
+    }
+}
+
+#[rustfmt::skip]
+impl<Env, From, To, Gas> TfnTestDexProxyMethods<Env, From, To, Gas>
+where
+    Env: TxEnv,
+    Env::Api: VMApi,
+    From: TxFrom<Env>,
+    To: TxTo<Env>,
+    Gas: TxGas<Env>,
+{
+    pub fn get_oracle_spot_price<
+        Arg0: ProxyArg<TokenIdentifier<Env::Api>>,
+        Arg1: ProxyArg<TokenIdentifier<Env::Api>>,
+        Arg2: ProxyArg<u64>,
+    >(
+        self,
+        token: Arg0,
+        base_token: Arg1,
+        max_staleness: Arg2,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getOracleSpotPrice")
+            .argument(&token)
+            .argument(&base_token)
+            .argument(&max_staleness)
+            .original_result()
+    }
+
+    pub fn get_oracle_twap<
+        Arg0: ProxyArg<TokenIdentifier<Env::Api>>,
+        Arg1: ProxyArg<TokenIdentifier<Env::Api>>,
+        Arg2: ProxyArg<u64>,
+        Arg3: ProxyArg<u64>,
+    >(
+        self,
+        token: Arg0,
+        base_token: Arg1,
+        period: Arg2,
+        max_staleness: Arg3,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getOracleTwap")
+            .argument(&token)
+            .argument(&base_token)
+            .argument(&period)
+            .argument(&max_staleness)
+            .original_result()
+    }
+
+    pub fn get_oracle_route_view<
+        Arg0: ProxyArg<TokenIdentifier<Env::Api>>,
+        Arg1: ProxyArg<TokenIdentifier<Env::Api>>,
+    >(
+        self,
+        token: Arg0,
+        base_token: Arg1,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, ManagedVec<Env::Api, Pair<Env::Api>>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getOracleRoute")
+            .argument(&token)
+            .argument(&base_token)
+            .original_result()
+    }
+
+    pub fn oracle_min_liquidity<
+        Arg0: ProxyArg<TokenIdentifier<Env::Api>>,
+    >(
+        self,
+        token: Arg0,
+    ) -> TxTypedCall<Env, From, To, NotPayable, Gas, BigUint<Env::Api>> {
+        self.wrapped_tx
+            .payment(NotPayable)
+            .raw_call("getOracleMinLiquidity")
+            .argument(&token)
+            .original_result()
+    }
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, PartialEq, Eq, Clone, Debug)]
+pub struct Pair<Api>
+where
+    Api: ManagedTypeApi,
+{
+    pub id: usize,
+    pub owner: ManagedAddress<Api>,
+    pub state: PairState,
+    pub token: TokenIdentifier<Api>,
+    pub base_token: TokenIdentifier<Api>,
+    pub lp_token: TokenIdentifier<Api>,
+    pub lp_supply: BigUint<Api>,
+    pub lp_fee: u64,
+    pub owner_fee: u64,
+    pub liquidity_token: BigUint<Api>,
+    pub liquidity_base: BigUint<Api>,
+    pub curve: Curve,
+    pub amp: AmpRamp,
+    pub token_weight: u64,
+    pub base_weight: u64,
+    pub token_decimals: u32,
+    pub max_price_impact: u64,
+    pub max_swap_ratio: u64,
+    pub token_risk: TokenRisk,
+    pub base_token_risk: TokenRisk,
+    pub base_decimals: u32,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PairState {
+    Inactive,
+    ActiveNoSwap,
+    Active,
+    Delisted,
+    PendingApproval,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Curve {
+    ConstantProduct,
+    StableSwap,
+    Weighted,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, PartialEq, Eq, Clone, Copy, Debug)]
+pub struct AmpRamp {
+    pub initial_amp: u64,
+    pub future_amp: u64,
+    pub initial_time: u64,
+    pub future_time: u64,
+}
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, ManagedVecItem, PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct TokenRisk {
+    pub inspected: bool,
+    pub can_pause: bool,
+    pub can_freeze: bool,
+    pub can_wipe: bool,
+    pub can_upgrade: bool,
+    pub can_change_owner: bool,
+    pub can_mint: bool,
+}
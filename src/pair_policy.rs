@@ -0,0 +1,122 @@
+use tfn_dex::common::{consts::*, errors::*};
+
+use crate::common::{self, config::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait PairPolicyModule:
+common::config::ConfigModule
+{
+    // 0 means no limit
+    #[endpoint(setMaxPairsPerOwner)]
+    fn set_max_pairs_per_owner(&self, max_pairs: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.max_pairs_per_owner().set(max_pairs);
+    }
+
+    #[endpoint(setMinInitialLiquidity)]
+    fn set_min_initial_liquidity(&self, base_token: TokenIdentifier, amount: BigUint) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(self.base_tokens().contains(&base_token), ERROR_WRONG_BASE_TOKEN);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.min_initial_liquidity(&base_token).set(amount);
+    }
+
+    #[endpoint(setPairApprovalRequired)]
+    fn set_pair_approval_required(&self, required: bool) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.pair_approval_required().set(required);
+    }
+
+    // addresses without a tier are in tier 0
+    #[endpoint(setStudentTier)]
+    fn set_student_tier(&self, address: ManagedAddress, tier: u8) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.student_tier(&address).set(tier);
+    }
+
+    #[endpoint(setTierFeeRange)]
+    fn set_tier_fee_range(&self, tier: u8, fee_range: FeeRange) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(
+            fee_range.min_lp_fee <= fee_range.max_lp_fee
+                && fee_range.min_owner_fee <= fee_range.max_owner_fee
+                && fee_range.max_lp_fee + fee_range.max_owner_fee < MAX_PERCENT,
+            ERROR_WRONG_FEE_RANGE
+        );
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.tier_fee_range(tier).set(fee_range);
+    }
+
+    #[endpoint(removeTierFeeRange)]
+    fn remove_tier_fee_range(&self, tier: u8) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.tier_fee_range(tier).clear();
+    }
+
+    // helpers
+    fn check_pair_creation_policy(&self, owner: &ManagedAddress, lp_fee: u64, owner_fee: u64) {
+        let max_pairs = self.max_pairs_per_owner().get();
+        if max_pairs > 0 {
+            let mut owned = 0;
+            for id in 0..self.last_pair_id().get() {
+                if !self.pair(id).is_empty() && &self.pair(id).get().owner == owner {
+                    owned += 1;
+                }
+                if !self.cl_pool(id).is_empty() && &self.cl_pool(id).get().owner == owner {
+                    owned += 1;
+                }
+            }
+            require!(owned < max_pairs, ERROR_TOO_MANY_PAIRS);
+        }
+
+        self.check_fee_range(owner, lp_fee, owner_fee);
+    }
+
+    fn check_fee_range(&self, owner: &ManagedAddress, lp_fee: u64, owner_fee: u64) {
+        let tier = self.student_tier(owner).get();
+        if self.tier_fee_range(tier).is_empty() {
+            return;
+        }
+
+        let fee_range = self.tier_fee_range(tier).get();
+        require!(
+            (fee_range.min_lp_fee..=fee_range.max_lp_fee).contains(&lp_fee)
+                && (fee_range.min_owner_fee..=fee_range.max_owner_fee).contains(&owner_fee),
+            ERROR_FEES_OUT_OF_RANGE
+        );
+    }
+
+    // storage
+    #[view(getMaxPairsPerOwner)]
+    #[storage_mapper("max_pairs_per_owner")]
+    fn max_pairs_per_owner(&self) -> SingleValueMapper<usize>;
+
+    // the base token amount required when adding a pair's initial liquidity
+    #[view(getMinInitialLiquidity)]
+    #[storage_mapper("min_initial_liquidity")]
+    fn min_initial_liquidity(&self, base_token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    #[view(getPairApprovalRequired)]
+    #[storage_mapper("pair_approval_required")]
+    fn pair_approval_required(&self) -> SingleValueMapper<bool>;
+
+    #[view(getStudentTier)]
+    #[storage_mapper("student_tiers")]
+    fn student_tier(&self, address: &ManagedAddress) -> SingleValueMapper<u8>;
+
+    #[view(getTierFeeRange)]
+    #[storage_mapper("tier_fee_ranges")]
+    fn tier_fee_range(&self, tier: u8) -> SingleValueMapper<FeeRange>;
+}
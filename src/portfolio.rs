@@ -0,0 +1,68 @@
+use crate::common::{self, config::*, consts::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait PortfolioModule:
+common::config::ConfigModule
++super::helpers::HelpersModule
++super::observations::ObservationsModule
++super::oracle::OracleModule
+{
+    // values the address' balances of the listed tokens at the current spot prices, LP tokens being valued as their share of
+    // the pair's reserves; tokens that cannot be priced are listed with a zero value and left out of the total
+    #[view(getPortfolioValue)]
+    fn get_portfolio_value(
+        &self,
+        address: ManagedAddress,
+        tokens: ManagedVec<TokenIdentifier>,
+        valuation_base: TokenIdentifier,
+    ) -> PortfolioValue<Self::Api> {
+        let mut portfolio = PortfolioValue {
+            total: BigUint::zero(),
+            tokens: ManagedVec::new(),
+        };
+        for token in tokens.iter() {
+            let balance = self.blockchain().get_esdt_balance(&address, &token, 0);
+            let value = match self.get_pair_by_lp_token(&token) {
+                Some(pair) => self.get_lp_value(&pair, &balance, &valuation_base),
+                None => self.get_token_value(&token, &balance, &valuation_base),
+            };
+            if let Some(value) = &value {
+                portfolio.total += value;
+            }
+            portfolio.tokens.push(TokenValue {
+                token: token.clone_value(),
+                balance,
+                priced: value.is_some(),
+                value: value.unwrap_or_default(),
+            });
+        }
+
+        portfolio
+    }
+
+    // helpers
+    fn get_token_value(&self, token: &TokenIdentifier, amount: &BigUint, valuation_base: &TokenIdentifier) -> Option<BigUint> {
+        if amount == &0 {
+            return Some(BigUint::zero());
+        }
+
+        let route = self.find_oracle_route(token, valuation_base)?;
+
+        Some(amount * &self.get_route_spot_price(token, &route) / WAD)
+    }
+
+    fn get_lp_value(&self, pair: &Pair<Self::Api>, lp_amount: &BigUint, valuation_base: &TokenIdentifier) -> Option<BigUint> {
+        if lp_amount == &0 || pair.lp_supply == 0 {
+            return Some(BigUint::zero());
+        }
+
+        let token_amount = lp_amount * &pair.liquidity_token / &pair.lp_supply;
+        let base_amount = lp_amount * &pair.liquidity_base / &pair.lp_supply;
+        let token_value = self.get_token_value(&pair.token, &token_amount, valuation_base)?;
+        let base_value = self.get_token_value(&pair.base_token, &base_amount, valuation_base)?;
+
+        Some(token_value + base_value)
+    }
+}
@@ -0,0 +1,125 @@
+use tfn_dex::common::{consts::*, errors::*};
+
+use crate::common::{self, config::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait ReferralModule:
+common::config::ConfigModule
++super::reserves::ReservesModule
+{
+    #[endpoint(addReferrer)]
+    fn add_referrer(&self, referrer: ManagedAddress) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.referrers().contains(&referrer), ERROR_REFERRER_EXISTS);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.referrers().insert(referrer);
+    }
+
+    // earnings accrued before the removal can still be claimed
+    #[endpoint(removeReferrer)]
+    fn remove_referrer(&self, referrer: ManagedAddress) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(self.referrers().contains(&referrer), ERROR_REFERRER_NOT_FOUND);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.referrers().swap_remove(&referrer);
+    }
+
+    #[endpoint(setReferralFeeShare)]
+    fn set_referral_fee_share(&self, share: u64) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(share <= MAX_PERCENT, ERROR_WRONG_REFERRAL_FEE_SHARE);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        self.referral_fee_share().set(share);
+    }
+
+    #[endpoint(claimReferralEarnings)]
+    fn claim_referral_earnings(&self) {
+        let state = self.state().get();
+        require!(state == State::Active || state == State::Emergency, ERROR_NOT_ACTIVE);
+
+        let caller = self.blockchain().get_caller();
+        let earnings = self.get_referral_earnings(caller.clone());
+        require!(!earnings.is_empty(), ERROR_NO_REFERRAL_EARNINGS);
+
+        for payment in earnings.iter() {
+            self.decrease_token_reserve(&payment.token_identifier, &payment.amount);
+            self.change_referral_total(&payment.token_identifier, &payment.amount, false);
+        }
+        self.referral_earnings(&caller).clear();
+        self.send().direct_multi(&caller, &earnings);
+    }
+
+    // views
+    #[view(getReferrers)]
+    fn get_referrers(&self) -> ManagedVec<ManagedAddress> {
+        let mut referrers = ManagedVec::new();
+        for referrer in self.referrers().iter() {
+            referrers.push(referrer);
+        }
+
+        referrers
+    }
+
+    #[view(getReferralEarnings)]
+    fn get_referral_earnings(&self, referrer: ManagedAddress) -> ManagedVec<EsdtTokenPayment> {
+        let mut earnings = ManagedVec::new();
+        for (token, amount) in self.referral_earnings(&referrer).iter() {
+            earnings.push(EsdtTokenPayment::new(token, 0, amount));
+        }
+
+        earnings
+    }
+
+    // helpers
+    // registered referrers get their share of the owner fee, the rest going to the pair or pool owner
+    fn pay_owner_fee(&self, owner: &ManagedAddress, token: &TokenIdentifier, owner_fee: &BigUint, referrer: &Option<ManagedAddress>) {
+        let mut owner_fee = owner_fee.clone();
+        if let Some(referrer) = referrer {
+            if self.referrers().contains(referrer) {
+                let referral_fee = &owner_fee * self.referral_fee_share().get() / MAX_PERCENT;
+                if referral_fee > 0 {
+                    owner_fee -= &referral_fee;
+                    let earnings = self.referral_earnings(referrer).get(token).unwrap_or_else(BigUint::zero);
+                    self.referral_earnings(referrer).insert(token.clone(), earnings + &referral_fee);
+                    self.change_referral_total(token, &referral_fee, true);
+                    self.increase_token_reserve(token, &referral_fee);
+                }
+            }
+        }
+
+        if owner_fee > 0 {
+            self.send().direct_esdt(owner, token, 0, &owner_fee);
+        }
+    }
+
+    fn change_referral_total(&self, token: &TokenIdentifier, amount: &BigUint, increase: bool) {
+        let total = self.referral_totals().get(token).unwrap_or_else(BigUint::zero);
+        let total = if increase { total + amount } else { total - amount };
+        if total == 0 {
+            self.referral_totals().remove(token);
+        } else {
+            self.referral_totals().insert(token.clone(), total);
+        }
+    }
+
+    // storage
+    #[storage_mapper("referrers")]
+    fn referrers(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    // in basis points of the owner fee
+    #[view(getReferralFeeShare)]
+    #[storage_mapper("referral_fee_share")]
+    fn referral_fee_share(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("referral_earnings")]
+    fn referral_earnings(&self, referrer: &ManagedAddress) -> MapMapper<TokenIdentifier, BigUint>;
+
+    // the earnings of all referrers, per token
+    #[storage_mapper("referral_totals")]
+    fn referral_totals(&self) -> MapMapper<TokenIdentifier, BigUint>;
+}
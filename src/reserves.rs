@@ -0,0 +1,111 @@
+use tfn_dex::common::errors::*;
+
+use crate::common::{self, config::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait ReservesModule:
+common::config::ConfigModule
++super::helpers::HelpersModule
++super::observations::ObservationsModule
+{
+    #[endpoint(skim)]
+    fn skim(&self, token: TokenIdentifier, to: ManagedAddress) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+
+        let surplus = self.get_token_surplus(&token);
+        require!(surplus > 0, ERROR_NOTHING_TO_SKIM);
+
+        self.send().direct_esdt(&to, &token, 0, &surplus);
+    }
+
+    #[endpoint(sync)]
+    fn sync(&self, pair_id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+        self.only_subscriber(&self.blockchain().get_caller());
+
+        let mut pair = self.pair(pair_id).get();
+        require!(pair.lp_supply > 0, ERROR_NO_LIQUIDITY);
+
+        let token_surplus = self.get_token_surplus(&pair.token);
+        let base_surplus = self.get_token_surplus(&pair.base_token);
+        require!(token_surplus > 0 || base_surplus > 0, ERROR_NOTHING_TO_SYNC);
+
+        pair.liquidity_token += token_surplus;
+        pair.liquidity_base += base_surplus;
+        self.update_pair(&pair);
+        self.update_price_observation(&pair);
+    }
+
+    // views
+    #[view(getTokenReserve)]
+    fn get_token_reserve(&self, token: TokenIdentifier) -> TokenReserve<Self::Api> {
+        let booked = self.token_reserves().get(&token).unwrap_or_else(BigUint::zero);
+        let balance = self.blockchain().get_sc_balance(&EgldOrEsdtTokenIdentifier::esdt(token.clone()), 0);
+
+        TokenReserve {
+            token,
+            booked,
+            balance,
+        }
+    }
+
+    #[view(getTokenReserves)]
+    fn get_token_reserves(&self) -> ManagedVec<TokenReserve<Self::Api>> {
+        let mut reserves = ManagedVec::new();
+        for token in self.token_reserves().keys() {
+            reserves.push(self.get_token_reserve(token));
+        }
+
+        reserves
+    }
+
+    // helpers
+    fn get_token_surplus(&self, token: &TokenIdentifier) -> BigUint {
+        let reserve = self.get_token_reserve(token.clone());
+        if reserve.balance > reserve.booked {
+            reserve.balance - reserve.booked
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    // stores the pair, booking the changes of its reserves
+    fn update_pair(&self, pair: &Pair<Self::Api>) {
+        let old_pair = self.pair(pair.id).get();
+        self.book_reserve_change(&pair.token, &old_pair.liquidity_token, &pair.liquidity_token);
+        self.book_reserve_change(&pair.base_token, &old_pair.liquidity_base, &pair.liquidity_base);
+        self.pair(pair.id).set(pair);
+    }
+
+    fn book_reserve_change(&self, token: &TokenIdentifier, old_amount: &BigUint, new_amount: &BigUint) {
+        if new_amount > old_amount {
+            self.increase_token_reserve(token, &(new_amount - old_amount));
+        } else if old_amount > new_amount {
+            self.decrease_token_reserve(token, &(old_amount - new_amount));
+        }
+    }
+
+    fn increase_token_reserve(&self, token: &TokenIdentifier, amount: &BigUint) {
+        if amount == &BigUint::zero() {
+            return;
+        }
+
+        let reserve = self.token_reserves().get(token).unwrap_or_else(BigUint::zero) + amount;
+        self.token_reserves().insert(token.clone(), reserve);
+    }
+
+    fn decrease_token_reserve(&self, token: &TokenIdentifier, amount: &BigUint) {
+        let reserve = self.token_reserves().get(token).unwrap_or_else(BigUint::zero) - amount;
+        if reserve == 0 {
+            self.token_reserves().remove(token);
+        } else {
+            self.token_reserves().insert(token.clone(), reserve);
+        }
+    }
+
+    #[storage_mapper("token_reserves")]
+    fn token_reserves(&self) -> MapMapper<TokenIdentifier, BigUint>;
+}
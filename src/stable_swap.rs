@@ -0,0 +1,88 @@
+use tfn_dex::common::errors::*;
+
+use crate::common::{self, config::*, consts::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait StableSwapModule:
+common::config::ConfigModule
+{
+    #[endpoint(rampAmplification)]
+    fn ramp_amplification(
+        &self,
+        id: usize,
+        future_amp: u64,
+        future_time: u64,
+    ) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let mut pair = self.pair(id).get();
+        require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(pair.curve == Curve::StableSwap, ERROR_NOT_STABLE_SWAP);
+        require!((MIN_AMP..=MAX_AMP).contains(&future_amp), ERROR_WRONG_AMP);
+
+        let now = self.blockchain().get_block_timestamp();
+        require!(now >= pair.amp.initial_time + MIN_RAMP_TIME, ERROR_RAMP_TOO_SOON);
+        require!(future_time >= now + MIN_RAMP_TIME, ERROR_RAMP_TOO_SHORT);
+
+        let current_amp = self.get_current_amp(&pair.amp);
+        if future_amp > current_amp {
+            require!(future_amp <= current_amp * MAX_AMP_CHANGE, ERROR_AMP_CHANGE_TOO_LARGE);
+        } else {
+            require!(future_amp * MAX_AMP_CHANGE >= current_amp, ERROR_AMP_CHANGE_TOO_LARGE);
+        }
+
+        pair.amp = AmpRamp {
+            initial_amp: current_amp,
+            future_amp,
+            initial_time: now,
+            future_time,
+        };
+        self.pair(id).set(pair);
+    }
+
+    #[endpoint(stopRampAmplification)]
+    fn stop_ramp_amplification(&self, id: usize) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let mut pair = self.pair(id).get();
+        require!(pair.owner == self.blockchain().get_caller(), ERROR_NOT_PAIR_OWNER);
+        require!(pair.curve == Curve::StableSwap, ERROR_NOT_STABLE_SWAP);
+
+        let now = self.blockchain().get_block_timestamp();
+        let current_amp = self.get_current_amp(&pair.amp);
+        pair.amp = AmpRamp {
+            initial_amp: current_amp,
+            future_amp: current_amp,
+            initial_time: now,
+            future_time: now,
+        };
+        self.pair(id).set(pair);
+    }
+
+    #[view(getAmplification)]
+    fn get_amplification(&self, id: usize) -> u64 {
+        require!(!self.pair(id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        self.get_current_amp(&self.pair(id).get().amp)
+    }
+
+    // helpers
+    fn get_current_amp(&self, amp: &AmpRamp) -> u64 {
+        let now = self.blockchain().get_block_timestamp();
+        if now >= amp.future_time {
+            return amp.future_amp;
+        }
+
+        let elapsed = now - amp.initial_time;
+        let duration = amp.future_time - amp.initial_time;
+        if amp.future_amp > amp.initial_amp {
+            amp.initial_amp + (amp.future_amp - amp.initial_amp) * elapsed / duration
+        } else {
+            amp.initial_amp - (amp.initial_amp - amp.future_amp) * elapsed / duration
+        }
+    }
+}
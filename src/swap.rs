@@ -1,6 +1,7 @@
-use tfn_dex::common::errors::*;
+use tfn_dex::common::{consts::*, errors::*};
 
-use crate::common::{self, config::*};
+use crate::{common::{self, config::*, errors::*}, concentrated::ClSwapResult, helpers::CurveParams};
+use tfn_test_dex_core::{swap, SwapResult};
 
 multiversx_sc::imports!();
 
@@ -8,117 +9,296 @@ multiversx_sc::imports!();
 pub trait SwapModule:
 common::config::ConfigModule
 +super::helpers::HelpersModule
++super::concentrated::ConcentratedModule
++super::observations::ObservationsModule
++super::dynamic_fee::DynamicFeeModule
++super::reserves::ReservesModule
++super::circuit_breaker::CircuitBreakerModule
++super::wegld::WegldModule
++super::referral::ReferralModule
++super::history::HistoryModule
 {
     #[payable("*")]
     #[endpoint(swapFixedInput)]
     fn swap_fixed_input(
         &self,
-        token_out: TokenIdentifier,
+        token_out: EgldOrEsdtTokenIdentifier,
         min_amount_out: BigUint,
+        referrer: OptionalValue<ManagedAddress>,
     ) {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
 
-        let payment = self.call_value().single_esdt();
-        let mut pair = match self.get_pair_by_tickers(&payment.token_identifier, &token_out) {
-            Some(pair) => pair,
-            None => sc_panic!(ERROR_PAIR_NOT_FOUND),
-        };
-        require!(pair.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
-
-        let fee_in = payment.token_identifier == pair.base_token;
-        let (amount_out, new_token_liquidity, new_base_liquidity, owner_fee) =
-            if token_out == pair.base_token {
-                self.do_swap_fixed_input(
-                    &payment.amount,
-                    &pair.liquidity_token,
-                    &pair.liquidity_base,
-                    fee_in,
-                    pair.lp_fee,
-                    pair.owner_fee,
-                )
-            } else {
-                let (amount_out, new_base_liquidity, new_token_liquidity, owner_fee) =
-                    self.do_swap_fixed_input(
-                        &payment.amount,
-                        &pair.liquidity_base,
-                        &pair.liquidity_token,
-                        fee_in,
-                        pair.lp_fee,
-                        pair.owner_fee,
-                    );
-                (amount_out, new_token_liquidity, new_base_liquidity, owner_fee)
-            };
-        require!(amount_out >= min_amount_out, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
-
-        self.send().direct_esdt(
-            &pair.owner,
-            &pair.base_token,
-            0,
-            &owner_fee,
+        let (payment, _) = self.get_wrapped_payment();
+        let output = self.swap_fixed_input_internal(
+            &payment,
+            &self.get_esdt_token(&token_out),
+            &min_amount_out,
+            &referrer.into_option(),
         );
-        pair.liquidity_token = new_token_liquidity;
-        pair.liquidity_base = new_base_liquidity;
-        self.pair(pair.id).set(&pair);
 
-        self.send().direct_esdt(&self.blockchain().get_caller(), &token_out, 0, &amount_out);
+        let mut outputs = ManagedVec::new();
+        let mut egld = BigUint::zero();
+        self.add_output(&mut outputs, &mut egld, output, token_out.is_egld());
+        self.send_outputs(&self.blockchain().get_caller(), &outputs, &egld);
     }
 
     #[payable("*")]
     #[endpoint(swapFixedOutput)]
     fn swap_fixed_output(
         &self,
-        token_out: TokenIdentifier,
+        token_out: EgldOrEsdtTokenIdentifier,
         amount_out_wanted: BigUint,
+        referrer: OptionalValue<ManagedAddress>,
     ) {
         require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
 
-        let payment = self.call_value().single_esdt();
-        let mut pair = match self.get_pair_by_tickers(&payment.token_identifier, &token_out) {
+        let (payment, paid_egld) = self.get_wrapped_payment();
+        let (output, refund) = self.swap_fixed_output_internal(
+            &payment,
+            &self.get_esdt_token(&token_out),
+            &amount_out_wanted,
+            &referrer.into_option(),
+        );
+
+        let mut outputs = ManagedVec::new();
+        let mut egld = BigUint::zero();
+        self.add_output(&mut outputs, &mut egld, output, token_out.is_egld());
+        self.add_output(&mut outputs, &mut egld, refund, paid_egld);
+        self.send_outputs(&self.blockchain().get_caller(), &outputs, &egld);
+    }
+
+    #[payable("*")]
+    #[endpoint(batchSwap)]
+    fn batch_swap(&self, instructions: MultiValueEncoded<SwapInstruction<Self::Api>>) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+
+        let (payments, paid_egld) = self.get_wrapped_payments();
+        require!(payments.len() == instructions.len(), ERROR_WRONG_BATCH_SWAP);
+
+        let mut outputs: ManagedVec<EsdtTokenPayment> = ManagedVec::new();
+        let mut egld = BigUint::zero();
+        for (index, (payment, instruction)) in payments.iter().zip(instructions.into_iter()).enumerate() {
+            let token_out = self.get_esdt_token(&instruction.token_out);
+            let unwrap_output = instruction.token_out.is_egld();
+            match instruction.kind {
+                SwapKind::FixedInput => {
+                    let output = self.swap_fixed_input_internal(&payment, &token_out, &instruction.limit, &None);
+                    self.add_output(&mut outputs, &mut egld, output, unwrap_output);
+                }
+                SwapKind::FixedOutput => {
+                    let (output, refund) = self.swap_fixed_output_internal(&payment, &token_out, &instruction.limit, &None);
+                    self.add_output(&mut outputs, &mut egld, output, unwrap_output);
+                    self.add_output(&mut outputs, &mut egld, refund, paid_egld.get(index));
+                }
+            }
+        }
+
+        self.send_outputs(&self.blockchain().get_caller(), &outputs, &egld);
+    }
+
+    // swaps the payment and returns the output, the owner fee being sent to the pair owner (and the referrer)
+    fn swap_fixed_input_internal(
+        &self,
+        payment: &EsdtTokenPayment,
+        token_out: &TokenIdentifier,
+        min_amount_out: &BigUint,
+        referrer: &Option<ManagedAddress>,
+    ) -> EsdtTokenPayment {
+        require!(
+            !self.denied_tokens().contains(&payment.token_identifier) && !self.denied_tokens().contains(token_out),
+            ERROR_TOKEN_DENIED
+        );
+
+        let mut pair = match self.get_pair_by_tickers(&payment.token_identifier, token_out) {
             Some(pair) => pair,
-            None => sc_panic!(ERROR_PAIR_NOT_FOUND),
+            None => {
+                let pool = match self.get_cl_pool_by_tickers(&payment.token_identifier, token_out) {
+                    Some(pool) => pool,
+                    None => sc_panic!(ERROR_PAIR_NOT_FOUND),
+                };
+                let result = self.swap_cl_routed(pool, payment, min_amount_out, true, referrer);
+
+                return EsdtTokenPayment::new(token_out.clone(), 0, result.amount_out);
+            }
         };
         require!(pair.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
 
         let fee_in = payment.token_identifier == pair.base_token;
-        let (amount_in, new_token_liquidity, new_base_liquidity, owner_fee) =
-            if token_out == pair.base_token {
-                self.do_swap_fixed_output(
-                    &amount_out_wanted,
-                    &pair.liquidity_token,
-                    &pair.liquidity_base,
-                    fee_in,
-                    pair.lp_fee,
-                    pair.owner_fee,
-                )
-            } else {
-                let (amount_in, new_base_liquidity, new_token_liquidity, owner_fee) =
-                    self.do_swap_fixed_output(
-                        &amount_out_wanted,
-                        &pair.liquidity_base,
-                        &pair.liquidity_token,
-                        fee_in,
-                        pair.lp_fee,
-                        pair.owner_fee,
-    );
-
-                (amount_in, new_token_liquidity, new_base_liquidity, owner_fee)
-            };
-        require!(amount_in > BigUint::zero() && amount_in <= payment.amount, ERROR_INSUFFICIENT_INPUT_AMOUNT);
-
-        self.send().direct_esdt(
-            &pair.owner,
-            &pair.base_token,
-            0,
-            &owner_fee,
+        let params = self.get_curve_params(&pair, fee_in);
+        let lp_fee = self.get_effective_lp_fee(&pair);
+        let token_is_input = token_out == &pair.base_token;
+        let result = if token_is_input {
+            self.do_swap_fixed_input(
+                &payment.amount,
+                &pair.liquidity_token,
+                &pair.liquidity_base,
+                fee_in,
+                lp_fee,
+                pair.owner_fee,
+                &params,
+            )
+        } else {
+            self.do_swap_fixed_input(
+                &payment.amount,
+                &pair.liquidity_base,
+                &pair.liquidity_token,
+                fee_in,
+                lp_fee,
+                pair.owner_fee,
+                &params,
+            )
+        };
+        require!(&result.amount >= min_amount_out, ERROR_INSUFFICIENT_OUTPUT_AMOUNT);
+        let old_pair = pair.clone();
+
+        self.pay_owner_fee(&pair.owner, &pair.base_token, &result.owner_fee, referrer);
+        self.set_pair_liquidity(&mut pair, token_is_input, &result);
+        self.check_swap_limits(&old_pair, &pair, &payment.amount, token_is_input);
+        self.check_circuit_breaker(&old_pair, &mut pair);
+        self.update_pair(&pair);
+        self.update_price_observation(&pair);
+        self.update_fee_window(pair.id);
+        let (kind, token_amount, base_amount) = if token_is_input {
+            (TradeKind::Sell, &payment.amount, &result.amount)
+        } else {
+            (TradeKind::Buy, &result.amount, &payment.amount)
+        };
+        self.record_trade(pair.id, kind, token_amount, base_amount);
+        self.record_swap_observation(&pair, base_amount);
+
+        EsdtTokenPayment::new(token_out.clone(), 0, result.amount)
+    }
+
+    // swaps the payment and returns the output along with the unused part of the payment
+    fn swap_fixed_output_internal(
+        &self,
+        payment: &EsdtTokenPayment,
+        token_out: &TokenIdentifier,
+        amount_out_wanted: &BigUint,
+        referrer: &Option<ManagedAddress>,
+    ) -> (EsdtTokenPayment, EsdtTokenPayment) {
+        require!(
+            !self.denied_tokens().contains(&payment.token_identifier) && !self.denied_tokens().contains(token_out),
+            ERROR_TOKEN_DENIED
         );
-        pair.liquidity_token = new_token_liquidity;
-        pair.liquidity_base = new_base_liquidity;
-        self.pair(pair.id).set(&pair);
-
-        let caller = self.blockchain().get_caller();
-        self.send().direct_esdt(&caller, &token_out, 0, &amount_out_wanted);
-        if amount_in < payment.amount {
-            self.send().direct_esdt(&caller, &payment.token_identifier, 0, &(payment.amount - amount_in));
+
+        let mut pair = match self.get_pair_by_tickers(&payment.token_identifier, token_out) {
+            Some(pair) => pair,
+            None => {
+                let pool = match self.get_cl_pool_by_tickers(&payment.token_identifier, token_out) {
+                    Some(pool) => pool,
+                    None => sc_panic!(ERROR_PAIR_NOT_FOUND),
+                };
+                let result = self.swap_cl_routed(pool, payment, amount_out_wanted, false, referrer);
+
+                return (
+                    EsdtTokenPayment::new(token_out.clone(), 0, result.amount_out),
+                    EsdtTokenPayment::new(payment.token_identifier.clone(), 0, &payment.amount - &result.amount_in),
+                );
+            }
+        };
+        require!(pair.state == PairState::Active, ERROR_PAIR_NOT_ACTIVE);
+
+        let fee_in = payment.token_identifier == pair.base_token;
+        let params = self.get_curve_params(&pair, fee_in);
+        let lp_fee = self.get_effective_lp_fee(&pair);
+        let token_is_input = token_out == &pair.base_token;
+        let result = if token_is_input {
+            self.do_swap_fixed_output(
+                amount_out_wanted,
+                &pair.liquidity_token,
+                &pair.liquidity_base,
+                fee_in,
+                lp_fee,
+                pair.owner_fee,
+                &params,
+            )
+        } else {
+            self.do_swap_fixed_output(
+                amount_out_wanted,
+                &pair.liquidity_base,
+                &pair.liquidity_token,
+                fee_in,
+                lp_fee,
+                pair.owner_fee,
+                &params,
+            )
+        };
+        require!(result.amount > 0 && result.amount <= payment.amount, ERROR_INSUFFICIENT_INPUT_AMOUNT);
+        let old_pair = pair.clone();
+
+        self.pay_owner_fee(&pair.owner, &pair.base_token, &result.owner_fee, referrer);
+        self.set_pair_liquidity(&mut pair, token_is_input, &result);
+        self.check_swap_limits(&old_pair, &pair, &result.amount, token_is_input);
+        self.check_circuit_breaker(&old_pair, &mut pair);
+        self.update_pair(&pair);
+        self.update_price_observation(&pair);
+        self.update_fee_window(pair.id);
+        let (kind, token_amount, base_amount) = if token_is_input {
+            (TradeKind::Sell, &result.amount, amount_out_wanted)
+        } else {
+            (TradeKind::Buy, amount_out_wanted, &result.amount)
+        };
+        self.record_trade(pair.id, kind, token_amount, base_amount);
+        self.record_swap_observation(&pair, base_amount);
+
+        (
+            EsdtTokenPayment::new(token_out.clone(), 0, amount_out_wanted.clone()),
+            EsdtTokenPayment::new(payment.token_identifier.clone(), 0, &payment.amount - &result.amount),
+        )
+    }
+
+    // concentrated liquidity pools have no swap limits, circuit breaker, dynamic fees or price observations,
+    // their trades are only recorded in the trader's history
+    fn swap_cl_routed(
+        &self,
+        pool: ClPool<Self::Api>,
+        payment: &EsdtTokenPayment,
+        amount: &BigUint,
+        exact_input: bool,
+        referrer: &Option<ManagedAddress>,
+    ) -> ClSwapResult<Self::Api> {
+        let pool_id = pool.id;
+        let owner = pool.owner.clone();
+        let base_in = payment.token_identifier == pool.base_token;
+        let result = self.swap_cl(pool, payment, amount, exact_input);
+
+        self.pay_owner_fee(&owner, &payment.token_identifier, &result.owner_fee, referrer);
+        let (kind, token_amount, base_amount) = if base_in {
+            (TradeKind::Buy, &result.amount_out, &result.amount_in)
+        } else {
+            (TradeKind::Sell, &result.amount_in, &result.amount_out)
+        };
+        self.record_trade(pool_id, kind, token_amount, base_amount);
+
+        result
+    }
+
+    // enforces the limits set by the pair owner, comparing the pair before and after the swap
+    fn check_swap_limits(
+        &self,
+        pair: &Pair<Self::Api>,
+        new_pair: &Pair<Self::Api>,
+        amount_in: &BigUint,
+        token_is_input: bool,
+    ) {
+        if pair.max_swap_ratio > 0 {
+            let liquidity_in = if token_is_input { &pair.liquidity_token } else { &pair.liquidity_base };
+            require!(amount_in * MAX_PERCENT <= liquidity_in * pair.max_swap_ratio, ERROR_SWAP_TOO_LARGE);
+        }
+        if pair.max_price_impact > 0 {
+            let price_impact = self.get_price_impact(&self.get_spot_price(pair), &self.get_spot_price(new_pair));
+            require!(price_impact <= pair.max_price_impact, ERROR_PRICE_IMPACT_TOO_HIGH);
+        }
+    }
+
+    fn set_pair_liquidity(&self, pair: &mut Pair<Self::Api>, token_is_input: bool, result: &SwapResult<BigUint>) {
+        if token_is_input {
+            pair.liquidity_token = result.new_liquidity_in.clone();
+            pair.liquidity_base = result.new_liquidity_out.clone();
+        } else {
+            pair.liquidity_base = result.new_liquidity_in.clone();
+            pair.liquidity_token = result.new_liquidity_out.clone();
         }
     }
 
@@ -130,26 +310,9 @@ common::config::ConfigModule
         fee_in: bool,
         lp_fee: u64,
         owner_fee: u64,
-    ) -> (BigUint, BigUint, BigUint, BigUint) {
-        if fee_in {
-            let (lp_fee, owner_fee, total_fee) =
-                self.get_fee_amounts(amount_in, true, lp_fee, owner_fee);
-            let left_amount_in = amount_in - &total_fee;
-            let amount_out = self.get_amount_out_no_fee(&left_amount_in, liquidity_in, liquidity_out);
-            let new_liquidity_in = liquidity_in + &left_amount_in + lp_fee;
-            let new_liquidity_out = liquidity_out - &amount_out;
-
-            (amount_out, new_liquidity_in, new_liquidity_out, owner_fee)
-        } else {
-            let amount_out = self.get_amount_out_no_fee(amount_in, liquidity_in, liquidity_out);
-            let (lp_fee, owner_fee, total_fee) =
-                self.get_fee_amounts(&amount_out, true, lp_fee, owner_fee);
-            let left_amount_out = &amount_out - &total_fee;
-            let new_liquidity_in = liquidity_in + amount_in;
-            let new_liquidity_out = liquidity_out - &amount_out + lp_fee;
-
-            (left_amount_out, new_liquidity_in, new_liquidity_out, owner_fee)
-        }
+        params: &CurveParams,
+    ) -> SwapResult<BigUint> {
+        self.unwrap_math(swap::do_swap_fixed_input(amount_in, liquidity_in, liquidity_out, fee_in, lp_fee, owner_fee, params))
     }
 
     fn do_swap_fixed_output(
@@ -160,25 +323,76 @@ common::config::ConfigModule
         fee_in: bool,
         lp_fee: u64,
         owner_fee: u64,
-    ) -> (BigUint, BigUint, BigUint, BigUint) {
-        if fee_in {
-            let amount_in_no_fee = self.get_amount_in_no_fee(amount_out, liquidity_in, liquidity_out);
-            let (lp_fee, owner_fee, total_fee) =
-                self.get_fee_amounts(&amount_in_no_fee, false, lp_fee, owner_fee);
-            let amount_in = &amount_in_no_fee + &total_fee;
-            let new_liquidity_in = liquidity_in + &amount_in_no_fee + lp_fee;
-            let new_liquidity_out = liquidity_out - amount_out;
-
-            (amount_in, new_liquidity_in, new_liquidity_out, owner_fee)
+        params: &CurveParams,
+    ) -> SwapResult<BigUint> {
+        self.unwrap_math(swap::do_swap_fixed_output(amount_out, liquidity_in, liquidity_out, fee_in, lp_fee, owner_fee, params))
+    }
+
+    #[view(getSpotPrice)]
+    fn get_spot_price_view(&self, pair_id: usize) -> BigUint {
+        require!(!self.pair(pair_id).is_empty(), ERROR_PAIR_NOT_FOUND);
+
+        let pair = self.pair(pair_id).get();
+        require!(pair.liquidity_token > 0 && pair.liquidity_base > 0, ERROR_NO_LIQUIDITY);
+
+        self.get_display_price(&pair, &self.get_spot_price(&pair))
+    }
+
+    #[view(getSwapQuote)]
+    fn get_swap_quote(
+        &self,
+        token_in: TokenIdentifier,
+        token_out: TokenIdentifier,
+        amount: BigUint,
+        kind: SwapKind,
+    ) -> SwapQuote<Self::Api> {
+        require!(amount > 0, ERROR_ZERO_AMOUNT);
+
+        let mut pair = match self.get_pair_by_tickers(&token_in, &token_out) {
+            Some(pair) => pair,
+            None => return self.get_cl_swap_quote(token_in, token_out, amount, kind),
+        };
+        require!(pair.liquidity_token > 0 && pair.liquidity_base > 0, ERROR_NO_LIQUIDITY);
+
+        let fee_in = token_in == pair.base_token;
+        let params = self.get_curve_params(&pair, fee_in);
+        let lp_fee = self.get_effective_lp_fee(&pair);
+        let token_is_input = token_in == pair.token;
+        let (liquidity_in, liquidity_out) = if token_is_input {
+            (pair.liquidity_token.clone(), pair.liquidity_base.clone())
         } else {
-            let (lp_fee, owner_fee, total_fee) =
-                self.get_fee_amounts(amount_out, false, lp_fee, owner_fee);
-            let left_amount_out = amount_out + &total_fee;
-            let amount_in = self.get_amount_in_no_fee(&left_amount_out, liquidity_in, liquidity_out);
-            let new_liquidity_in = liquidity_in + &amount_in;
-            let new_liquidity_out = liquidity_out - &left_amount_out + lp_fee;
-
-            (amount_in, new_liquidity_in, new_liquidity_out, owner_fee)
+            (pair.liquidity_base.clone(), pair.liquidity_token.clone())
+        };
+        let result = match kind {
+            SwapKind::FixedInput => {
+                self.do_swap_fixed_input(&amount, &liquidity_in, &liquidity_out, fee_in, lp_fee, pair.owner_fee, &params)
+            }
+            SwapKind::FixedOutput => {
+                self.do_swap_fixed_output(&amount, &liquidity_in, &liquidity_out, fee_in, lp_fee, pair.owner_fee, &params)
+            }
+        };
+        let (amount_in, amount_out) = match kind {
+            SwapKind::FixedInput => (amount, result.amount.clone()),
+            SwapKind::FixedOutput => (result.amount.clone(), amount),
+        };
+
+        let price_before = self.get_spot_price(&pair);
+        self.set_pair_liquidity(&mut pair, token_is_input, &result);
+        let price_after = self.get_spot_price(&pair);
+
+        SwapQuote {
+            pair_id: pair.id,
+            token_in,
+            token_out,
+            amount_in,
+            amount_out,
+            spot_price_before: self.get_display_price(&pair, &price_before),
+            spot_price_after: self.get_display_price(&pair, &price_after),
+            price_impact: self.get_price_impact(&price_before, &price_after),
+            lp_fee: result.lp_fee,
+            owner_fee: result.owner_fee,
+            new_liquidity_token: pair.liquidity_token,
+            new_liquidity_base: pair.liquidity_base,
         }
     }
 
@@ -193,18 +407,22 @@ common::config::ConfigModule
 
         let pair = match self.get_pair_by_tickers(token_in, token_out) {
             Some(pair) => pair,
-            None => sc_panic!(ERROR_PAIR_NOT_FOUND),
+            None => return self.get_cl_amount(token_in, token_out, &amount_in, true),
         };
         let fee_in = token_in == &pair.base_token;
-        if token_in == &pair.token {
+        let params = self.get_curve_params(&pair, fee_in);
+        let lp_fee = self.get_effective_lp_fee(&pair);
+        let result = if token_in == &pair.token {
             require!(pair.liquidity_base > 0, ERROR_NO_LIQUIDITY);
 
-            self.get_amount_out(&amount_in, &pair.liquidity_token, &pair.liquidity_base, fee_in, pair.lp_fee + pair.owner_fee)
+            swap::get_amount_out(&amount_in, &pair.liquidity_token, &pair.liquidity_base, fee_in, lp_fee, pair.owner_fee, &params)
         } else {
             require!(pair.liquidity_token > 0, ERROR_NO_LIQUIDITY);
 
-            self.get_amount_out(&amount_in, &pair.liquidity_base, &pair.liquidity_token, fee_in, pair.lp_fee + pair.owner_fee)
-        }
+            swap::get_amount_out(&amount_in, &pair.liquidity_base, &pair.liquidity_token, fee_in, lp_fee, pair.owner_fee, &params)
+        };
+
+        self.unwrap_math(result)
     }
 
     #[view(getAmountIn)]
@@ -218,17 +436,21 @@ common::config::ConfigModule
 
         let pair = match self.get_pair_by_tickers(token_in, token_out) {
             Some(pair) => pair,
-            None => sc_panic!(ERROR_PAIR_NOT_FOUND),
+            None => return self.get_cl_amount(token_in, token_out, &amount_out, false),
         };
         let fee_in = token_in == &pair.base_token;
-        if token_in == &pair.token {
+        let params = self.get_curve_params(&pair, fee_in);
+        let lp_fee = self.get_effective_lp_fee(&pair);
+        let result = if token_in == &pair.token {
             require!(pair.liquidity_base > 0, ERROR_NO_LIQUIDITY);
 
-            self.get_amount_in(&amount_out, &pair.liquidity_token, &pair.liquidity_base, fee_in, pair.lp_fee + pair.owner_fee)
+            swap::get_amount_in(&amount_out, &pair.liquidity_token, &pair.liquidity_base, fee_in, lp_fee, pair.owner_fee, &params)
         } else {
             require!(pair.liquidity_token > 0, ERROR_NO_LIQUIDITY);
 
-            self.get_amount_in(&amount_out, &pair.liquidity_base, &pair.liquidity_token, fee_in, pair.lp_fee + pair.owner_fee)
-        }
+            swap::get_amount_in(&amount_out, &pair.liquidity_base, &pair.liquidity_token, fee_in, lp_fee, pair.owner_fee, &params)
+        };
+
+        self.unwrap_math(result)
     }
 }
@@ -0,0 +1,121 @@
+use tfn_dex::common::errors::*;
+
+use crate::common::{self, config::*, consts::*, errors::*};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait TokenRiskModule:
+common::config::ConfigModule
+{
+    // asks the ESDT system SC what the issuer of the token can do and its decimals, tokens should be inspected before being listed
+    #[endpoint(inspectToken)]
+    fn inspect_token(&self, token: TokenIdentifier) {
+        require!(self.state().get() == State::Active, ERROR_NOT_ACTIVE);
+
+        self.tx()
+            .to(ESDTSystemSCAddress)
+            .raw_call("getTokenProperties")
+            .argument(&token)
+            .callback(self.callbacks().token_properties_callback(token))
+            .async_call_and_exit();
+    }
+
+    #[callback]
+    fn token_properties_callback(
+        &self,
+        token: TokenIdentifier,
+        #[call_result] result: ManagedAsyncCallResult<MultiValueEncoded<ManagedBuffer>>,
+    ) {
+        let properties = match result {
+            ManagedAsyncCallResult::Ok(properties) => properties,
+            ManagedAsyncCallResult::Err(_) => return,
+        };
+
+        let mut risk = TokenRisk {
+            inspected: true,
+            ..TokenRisk::default()
+        };
+        let mut decimals = 0u32;
+        let mut buffer = [0u8; 32];
+        for property in properties.into_iter() {
+            if property.len() > buffer.len() {
+                continue;
+            }
+
+            match property.load_to_byte_array(&mut buffer) {
+                b"CanPause-true" => risk.can_pause = true,
+                b"CanFreeze-true" => risk.can_freeze = true,
+                b"CanWipe-true" => risk.can_wipe = true,
+                b"CanUpgrade-true" => risk.can_upgrade = true,
+                b"CanChangeOwner-true" => risk.can_change_owner = true,
+                b"CanMint-true" => risk.can_mint = true,
+                bytes if bytes.starts_with(NUM_DECIMALS_PROPERTY) => {
+                    decimals = bytes[NUM_DECIMALS_PROPERTY.len()..]
+                        .iter()
+                        .fold(0, |value, digit| value * 10 + (digit - b'0') as u32);
+                }
+                _ => {}
+            }
+        }
+        self.token_risk(&token).set(risk);
+        self.inspected_decimals(&token).set(decimals);
+
+        // listed pairs show the refreshed information
+        for id in 0..self.last_pair_id().get() {
+            if self.pair(id).is_empty() {
+                continue;
+            }
+
+            let mut pair = self.pair(id).get();
+            if pair.token == token {
+                pair.token_risk = risk;
+                pair.token_decimals = decimals;
+            } else if pair.base_token == token {
+                pair.base_token_risk = risk;
+                pair.base_decimals = decimals;
+            } else {
+                continue;
+            }
+            self.pair(id).set(pair);
+        }
+    }
+
+    // helpers
+    // both tokens should have been inspected, the listed token should not be pausable or freezable by its issuer,
+    // base tokens being vouched for by the platform subscriber, their risk is only shown
+    fn check_token_risk(&self, token: &TokenIdentifier, base_token: &TokenIdentifier) {
+        require!(
+            !self.token_risk(token).is_empty() && !self.token_risk(base_token).is_empty(),
+            ERROR_TOKEN_NOT_INSPECTED
+        );
+        require!(
+            self.inspected_decimals(token).get() <= MAX_TOKEN_DECIMALS
+                && self.inspected_decimals(base_token).get() <= MAX_TOKEN_DECIMALS,
+            ERROR_WRONG_DECIMALS
+        );
+
+        let risk = self.token_risk(token).get();
+        require!(!risk.can_pause && !risk.can_freeze, ERROR_TOKEN_RISKY);
+    }
+
+    // tokens that cannot currently be moved in and out of the SC are rejected when listed or activated
+    fn check_token_transferable(&self, token: &TokenIdentifier) {
+        let sc_address = self.blockchain().get_sc_address();
+        require!(
+            !self.blockchain().is_esdt_paused(token)
+                && !self.blockchain().is_esdt_limited_transfer(token)
+                && !self.blockchain().is_esdt_frozen(&sc_address, token, 0),
+            ERROR_TOKEN_NOT_TRANSFERABLE
+        );
+    }
+
+    // storage
+    #[view(getTokenRisk)]
+    #[storage_mapper("token_risks")]
+    fn token_risk(&self, token: &TokenIdentifier) -> SingleValueMapper<TokenRisk>;
+
+    #[view(getTokenDecimals)]
+    #[storage_mapper("inspected_decimals")]
+    fn inspected_decimals(&self, token: &TokenIdentifier) -> SingleValueMapper<u32>;
+}
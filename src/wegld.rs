@@ -0,0 +1,123 @@
+use tfn_dex::common::errors::*;
+
+use crate::common::{self, consts::*, errors::*, wegld_proxy};
+
+multiversx_sc::imports!();
+
+#[multiversx_sc::module]
+pub trait WegldModule:
+common::config::ConfigModule
+{
+    #[only_owner]
+    #[endpoint(setWegldAddress)]
+    fn set_wegld_address(&self, wegld_sc: ManagedAddress) {
+        let wegld_token = self.wegld_contract_proxy()
+            .contract(wegld_sc.clone())
+            .wrapped_egld_token_id()
+            .execute_on_dest_context::<TokenIdentifier>();
+        self.wegld_sc().set(wegld_sc);
+        self.wegld_token().set(wegld_token);
+    }
+
+    #[view(getWegldAddress)]
+    #[storage_mapper("wegld_address")]
+    fn wegld_sc(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getWegldToken)]
+    #[storage_mapper("wegld_token")]
+    fn wegld_token(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    // helpers
+    // the payments, with native EGLD (sent alone or as EGLD-000000 in a multi-transfer) wrapped, and for each of them
+    // whether it was paid in EGLD, so that only its refunds are unwrapped
+    fn get_wrapped_payments(&self) -> (ManagedVec<EsdtTokenPayment>, ManagedVec<bool>) {
+        let mut payments = ManagedVec::new();
+        let mut paid_egld = ManagedVec::new();
+        match self.call_value().any_payment() {
+            EgldOrMultiEsdtPayment::Egld(amount) => {
+                if amount > 0 {
+                    payments.push(self.wrap_egld(&amount));
+                    paid_egld.push(true);
+                }
+            }
+            EgldOrMultiEsdtPayment::MultiEsdt(esdt_payments) => {
+                for payment in esdt_payments.iter() {
+                    if payment.token_identifier == TokenIdentifier::from(EGLD_TOKEN_IDENTIFIER) {
+                        payments.push(self.wrap_egld(&payment.amount));
+                        paid_egld.push(true);
+                    } else {
+                        payments.push(payment.clone());
+                        paid_egld.push(false);
+                    }
+                }
+            }
+        }
+
+        (payments, paid_egld)
+    }
+
+    fn get_wrapped_payment(&self) -> (EsdtTokenPayment, bool) {
+        let (payments, paid_egld) = self.get_wrapped_payments();
+        require!(payments.len() == 1, ERROR_WRONG_PAYMENT);
+
+        (payments.get(0).clone(), paid_egld.get(0))
+    }
+
+    // EGLD is traded through the WEGLD pairs
+    fn get_esdt_token(&self, token: &EgldOrEsdtTokenIdentifier) -> TokenIdentifier {
+        if token.is_egld() {
+            require!(!self.wegld_token().is_empty(), ERROR_WEGLD_NOT_SET);
+
+            self.wegld_token().get()
+        } else {
+            token.clone().unwrap_esdt()
+        }
+    }
+
+    fn wrap_egld(&self, amount: &BigUint) -> EsdtTokenPayment {
+        require!(!self.wegld_sc().is_empty(), ERROR_WEGLD_NOT_SET);
+
+        self.wegld_contract_proxy()
+            .contract(self.wegld_sc().get())
+            .wrap_egld()
+            .with_egld_transfer(amount.clone())
+            .execute_on_dest_context()
+    }
+
+    // adds the payment to the ones to be sent, WEGLD being unwrapped into `egld` if `unwrap` is set
+    fn add_output(
+        &self,
+        outputs: &mut ManagedVec<EsdtTokenPayment>,
+        egld: &mut BigUint,
+        payment: EsdtTokenPayment,
+        unwrap: bool,
+    ) {
+        if payment.amount == 0 {
+            return;
+        }
+
+        if unwrap && !self.wegld_token().is_empty() && payment.token_identifier == self.wegld_token().get() {
+            *egld += payment.amount;
+        } else {
+            outputs.push(payment);
+        }
+    }
+
+    fn send_outputs(&self, to: &ManagedAddress, outputs: &ManagedVec<EsdtTokenPayment>, egld: &BigUint) {
+        if *egld > 0 {
+            self.wegld_contract_proxy()
+                .contract(self.wegld_sc().get())
+                .unwrap_egld()
+                .with_esdt_transfer(EsdtTokenPayment::new(self.wegld_token().get(), 0, egld.clone()))
+                .execute_on_dest_context::<()>();
+            self.send().direct_egld(to, egld);
+        }
+        if !outputs.is_empty() {
+            self.send().direct_multi(to, outputs);
+        }
+    }
+
+    // proxies
+    #[proxy]
+    fn wegld_contract_proxy(&self) -> wegld_proxy::Proxy<Self::Api>;
+}
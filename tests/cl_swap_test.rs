@@ -0,0 +1,107 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::{cl_pool, world_with, BASE_TOKEN, DEX, OWNER, TOKEN};
+use tfn_test_dex::{
+    common::config::{ConfigModule, PairState, State, SwapKind, TradeKind},
+    concentrated::ConcentratedModule,
+    history::HistoryModule,
+    observations::ObservationsModule,
+    referral::ReferralModule,
+    reserves::ReservesModule,
+    swap::SwapModule,
+};
+
+const USER: TestAddress = TestAddress::new("user");
+const REFERRER: TestAddress = TestAddress::new("referrer");
+const LIQUIDITY: u64 = 1_000_000_000_000;
+const RESERVE: u64 = 100_000_000_000;
+const USER_BALANCE: u64 = 1_000_000_000;
+
+// an active pool of the test tokens at price 1, with one position between ticks -2000 and 2000,
+// a registered referrer earning half of the owner fee and a user holding both tokens
+fn cl_pool_world() -> ScenarioWorld {
+    let mut world = world_with(&[(TOKEN, RESERVE), (BASE_TOKEN, RESERVE)], &[]);
+    world
+        .account(USER)
+        .nonce(1)
+        .esdt_balance(TOKEN, USER_BALANCE)
+        .esdt_balance(BASE_TOKEN, USER_BALANCE);
+    world.account(REFERRER).nonce(1);
+
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        let pool = cl_pool(0, PairState::Active, LIQUIDITY, RESERVE, RESERVE);
+        sc.update_cl_tick(&pool, -2_000, &BigUint::from(LIQUIDITY), true, true);
+        sc.update_cl_tick(&pool, 2_000, &BigUint::from(LIQUIDITY), false, true);
+        sc.increase_token_reserve(&pool.token, &pool.reserve_token);
+        sc.increase_token_reserve(&pool.base_token, &pool.reserve_base);
+        sc.cl_pool(0).set(pool);
+        sc.last_pair_id().set(1);
+        sc.referrers().insert(REFERRER.to_managed_address());
+        sc.referral_fee_share().set(5_000);
+        sc.state().set(State::Active);
+    });
+
+    world
+}
+
+// the quote matches the swap, which pays the referrer in the payment token and is recorded in the user's history,
+// while the pair-only price observations are left untouched
+#[test]
+fn cl_routed_swap_records_trade_and_pays_referrer() {
+    let mut world = cl_pool_world();
+    let amount_in = 10_000_000u64;
+
+    let mut quoted_out = 0;
+    let mut quoted_owner_fee = 0;
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        let quote = sc.get_swap_quote(
+            BASE_TOKEN.to_token_identifier(),
+            TOKEN.to_token_identifier(),
+            BigUint::from(amount_in),
+            SwapKind::FixedInput,
+        );
+        assert!(quote.price_impact > 0);
+        quoted_out = quote.amount_out.to_u64().unwrap();
+        quoted_owner_fee = quote.owner_fee.to_u64().unwrap();
+    });
+    assert!(quoted_out > 0 && quoted_owner_fee > 0);
+
+    world
+        .tx()
+        .from(USER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, amount_in))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(
+                EgldOrEsdtTokenIdentifier::esdt(TOKEN.to_token_identifier()),
+                BigUint::zero(),
+                OptionalValue::Some(REFERRER.to_managed_address()),
+            );
+        });
+
+    world
+        .check_account(USER)
+        .esdt_balance(TOKEN, USER_BALANCE + quoted_out)
+        .esdt_balance(BASE_TOKEN, USER_BALANCE - amount_in);
+    let referral_fee = quoted_owner_fee / 2;
+    world.check_account(OWNER).esdt_balance(BASE_TOKEN, quoted_owner_fee - referral_fee);
+
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        assert_eq!(
+            sc.referral_earnings(&REFERRER.to_managed_address()).get(&BASE_TOKEN.to_token_identifier()),
+            Some(BigUint::from(referral_fee))
+        );
+
+        let user = USER.to_managed_address();
+        assert_eq!(sc.trade_count(&user).get(), 1);
+        let trade = sc.trade_record(&user, 0).get();
+        assert!(trade.pair_id == 0 && trade.kind == TradeKind::Buy);
+        assert_eq!(trade.token_amount, BigUint::from(quoted_out));
+        assert_eq!(trade.base_amount, BigUint::from(amount_in));
+
+        assert!(sc.price_observation(0).is_empty());
+        assert_eq!(sc.swap_observation_count(0).get(), 0);
+    });
+}
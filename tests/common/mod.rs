@@ -0,0 +1,120 @@
+#![allow(dead_code)]
+
+use multiversx_sc_scenario::imports::*;
+
+use tfn_test_dex::{
+    common::{
+        config::{AmpRamp, ClPool, ConfigModule, Curve, Pair, PairState, State, TokenRisk},
+        consts::WAD,
+    },
+    reserves::ReservesModule,
+};
+
+pub const CODE_PATH: MxscPath = MxscPath::new("output/tfn-test-dex.mxsc.json");
+pub const OWNER: TestAddress = TestAddress::new("owner");
+pub const DEX: TestSCAddress = TestSCAddress::new("dex");
+
+pub const TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("TOKEN-123456");
+pub const BASE_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("BASE-123456");
+pub const LP_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("TOKENBASE-123456");
+pub const POSITION_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("TOKENPos-123456");
+
+pub fn message(error: &[u8]) -> &str {
+    core::str::from_utf8(error).unwrap()
+}
+
+// the DEX deployed with empty storage, tests set what they need through whitebox calls
+pub fn world() -> ScenarioWorld {
+    world_with(&[], &[])
+}
+
+// the DEX deployed with empty storage, holding `balances` and having `roles` on its tokens;
+// redeclaring an account resets its storage, so the DEX account can't be completed after the first whitebox call
+pub fn world_with(balances: &[(TestTokenIdentifier, u64)], roles: &[(TestTokenIdentifier, &str)]) -> ScenarioWorld {
+    let mut world = ScenarioWorld::new();
+    world.register_contract(CODE_PATH, tfn_test_dex::ContractBuilder);
+    world.account(OWNER).nonce(1);
+    let mut dex = world.account(DEX).nonce(1).code(CODE_PATH).owner(OWNER);
+    for (token, amount) in balances {
+        dex = dex.esdt_balance(*token, *amount);
+    }
+    for (token, role) in roles {
+        dex = dex.esdt_roles(*token, vec![role.to_string()]);
+    }
+    drop(dex);
+
+    world
+}
+
+// stores the `pair` fixture with its liquidity accounted in the DEX reserves and sets the contract state
+pub fn set_pair(
+    world: &mut ScenarioWorld,
+    state: State,
+    pair_state: PairState,
+    liquidity_token: u64,
+    liquidity_base: u64,
+    lp_supply: u64,
+) {
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        let pair = pair(pair_state, liquidity_token, liquidity_base, lp_supply);
+        sc.increase_token_reserve(&pair.token, &pair.liquidity_token);
+        sc.increase_token_reserve(&pair.base_token, &pair.liquidity_base);
+        sc.pair(0).set(pair);
+        sc.last_pair_id().set(1);
+        sc.state().set(state);
+    });
+}
+
+// a constant product pair of the test tokens owned by OWNER, to be stored with id 0
+pub fn pair<M: ManagedTypeApi>(state: PairState, liquidity_token: u64, liquidity_base: u64, lp_supply: u64) -> Pair<M> {
+    Pair {
+        id: 0,
+        owner: OWNER.to_managed_address(),
+        state,
+        token: TOKEN.to_token_identifier(),
+        base_token: BASE_TOKEN.to_token_identifier(),
+        lp_token: LP_TOKEN.to_token_identifier(),
+        lp_supply: BigUint::from(lp_supply),
+        lp_fee: 30,
+        owner_fee: 10,
+        liquidity_token: BigUint::from(liquidity_token),
+        liquidity_base: BigUint::from(liquidity_base),
+        curve: Curve::ConstantProduct,
+        amp: AmpRamp {
+            initial_amp: 0,
+            future_amp: 0,
+            initial_time: 0,
+            future_time: 0,
+        },
+        token_weight: 5_000,
+        base_weight: 5_000,
+        token_decimals: 18,
+        max_price_impact: 0,
+        max_swap_ratio: 0,
+        token_risk: TokenRisk::default(),
+        base_token_risk: TokenRisk::default(),
+        base_decimals: 18,
+    }
+}
+
+// a concentrated liquidity pool of the test tokens owned by OWNER, at price 1 and without ticks, to be stored with `id`
+pub fn cl_pool<M: ManagedTypeApi>(id: usize, state: PairState, liquidity: u64, reserve_token: u64, reserve_base: u64) -> ClPool<M> {
+    ClPool {
+        id,
+        owner: OWNER.to_managed_address(),
+        state,
+        token: TOKEN.to_token_identifier(),
+        base_token: BASE_TOKEN.to_token_identifier(),
+        position_token: POSITION_TOKEN.to_token_identifier(),
+        lp_fee: 30,
+        owner_fee: 10,
+        tick_spacing: 10,
+        sqrt_price: BigUint::from(WAD),
+        current_tick: 0,
+        liquidity: BigUint::from(liquidity),
+        fee_growth_token: BigUint::zero(),
+        fee_growth_base: BigUint::zero(),
+        reserve_token: BigUint::from(reserve_token),
+        reserve_base: BigUint::from(reserve_base),
+    }
+}
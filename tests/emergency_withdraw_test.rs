@@ -0,0 +1,309 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::{cl_pool, message, set_pair, world_with, BASE_TOKEN, DEX, LP_TOKEN, OWNER, POSITION_TOKEN, TOKEN};
+use tfn_dex::common::errors::ERROR_NOT_ACTIVE;
+use tfn_test_dex::{
+    common::config::{ClPosition, ConfigModule, Farm, FarmPosition, LockPool, LockPosition, PairState, State},
+    concentrated::ConcentratedModule,
+    farm::FarmModule,
+    liquidity::LiquidityModule,
+    lock::LockModule,
+    reserves::ReservesModule,
+};
+use tfn_test_dex_core::testing::Rng;
+
+const LPS: [TestAddress; 3] = [
+    TestAddress::new("lp1"),
+    TestAddress::new("lp2"),
+    TestAddress::new("lp3"),
+];
+
+const LOCK_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("LOCKEDLP-123456");
+const REWARD_TOKEN: TestTokenIdentifier = TestTokenIdentifier::new("REWARD-123456");
+
+// each case deploys its own world, so fewer of them are run
+const WORLD_ITERATIONS: usize = 20;
+
+// a pair with the given reserves whose LP tokens are held by the LPS accounts, the contract being in emergency mode;
+// the DEX also holds the `held` tokens and can burn LP tokens, position and lock NFTs
+fn emergency_world(
+    pair_state: PairState,
+    liquidity_token: u64,
+    liquidity_base: u64,
+    lp_amounts: &[u64],
+    held: &[(TestTokenIdentifier, u64)],
+) -> ScenarioWorld {
+    let mut balances = vec![(TOKEN, liquidity_token), (BASE_TOKEN, liquidity_base)];
+    balances.extend_from_slice(held);
+    let mut world = world_with(
+        &balances,
+        &[(LP_TOKEN, "ESDTRoleLocalBurn"), (POSITION_TOKEN, "ESDTRoleNFTBurn"), (LOCK_TOKEN, "ESDTRoleNFTBurn")],
+    );
+    for (lp, amount) in LPS.iter().zip(lp_amounts) {
+        world.account(*lp).nonce(1).esdt_balance(LP_TOKEN, *amount);
+    }
+    set_pair(&mut world, State::Emergency, pair_state, liquidity_token, liquidity_base, lp_amounts.iter().sum());
+
+    world
+}
+
+fn remove_liquidity(world: &mut ScenarioWorld, lp: TestAddress, amount: u64) {
+    world
+        .tx()
+        .from(lp)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, amount))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.remove_liquidity(OptionalValue::None);
+        });
+}
+
+#[test]
+fn emergency_withdrawals_work_whatever_the_pair_state() {
+    for pair_state in [PairState::Inactive, PairState::ActiveNoSwap, PairState::Active, PairState::Delisted] {
+        let mut world = emergency_world(pair_state, 1_000_000, 4_000_000, &[1_000], &[]);
+        remove_liquidity(&mut world, LPS[0], 1_000);
+
+        world
+            .check_account(LPS[0])
+            .esdt_balance(TOKEN, 1_000_000u64)
+            .esdt_balance(BASE_TOKEN, 4_000_000u64)
+            .esdt_balance(LP_TOKEN, 0u64);
+        world
+            .check_account(DEX)
+            .esdt_balance(TOKEN, 0u64)
+            .esdt_balance(BASE_TOKEN, 0u64);
+    }
+}
+
+// however the LPs split their withdrawals, everything held for the pair is paid out once all LP tokens are burned
+#[test]
+fn all_funds_are_recoverable() {
+    let mut rng = Rng(0x5eed_4001);
+    for _ in 0..WORLD_ITERATIONS {
+        let liquidity_token = rng.range(1, 1_000_000_000_000_000_000);
+        let liquidity_base = rng.range(1, 1_000_000_000_000_000_000);
+        let lp_amounts: Vec<u64> = LPS.iter().map(|_| rng.range(1, 1_000_000_000_000)).collect();
+        let mut world = emergency_world(PairState::Active, liquidity_token, liquidity_base, &lp_amounts, &[]);
+
+        let mut left = lp_amounts.clone();
+        while left.iter().any(|amount| *amount > 0) {
+            let index = rng.range(0, LPS.len() as u64 - 1) as usize;
+            if left[index] == 0 {
+                continue;
+            }
+
+            let amount = rng.range(1, left[index]);
+            remove_liquidity(&mut world, LPS[index], amount);
+            left[index] -= amount;
+        }
+
+        world
+            .check_account(DEX)
+            .esdt_balance(TOKEN, 0u64)
+            .esdt_balance(BASE_TOKEN, 0u64);
+        world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+            let pair = sc.pair(0).get();
+            assert_eq!(pair.lp_supply, 0u64);
+            assert_eq!(pair.liquidity_token, 0u64);
+            assert_eq!(pair.liquidity_base, 0u64);
+            assert!(sc.token_reserves().is_empty());
+        });
+    }
+}
+
+#[test]
+fn emergency_blocks_adding_liquidity() {
+    let mut world = emergency_world(PairState::Active, 1_000_000, 1_000_000, &[1_000], &[]);
+    world.account(LPS[1]).nonce(1).esdt_balance(TOKEN, 1_000u64).esdt_balance(BASE_TOKEN, 1_000u64);
+
+    world
+        .tx()
+        .from(LPS[1])
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, 1_000))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 1_000))
+        .returns(ExpectMessage(message(ERROR_NOT_ACTIVE)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.add_liquidity();
+        });
+}
+
+#[test]
+fn inactive_state_blocks_withdrawals() {
+    let mut world = emergency_world(PairState::Active, 1_000_000, 1_000_000, &[1_000], &[]);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.set_state_inactive();
+        assert!(sc.state().get() == State::Inactive);
+    });
+
+    world
+        .tx()
+        .from(LPS[0])
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LP_TOKEN, 0, 1_000))
+        .returns(ExpectMessage(message(ERROR_NOT_ACTIVE)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.remove_liquidity(OptionalValue::None);
+        });
+}
+
+// an inactive pool at price 1 whose only position, between ticks -2000 and 2000, is held by the first LP
+#[test]
+fn emergency_withdraws_cl_positions() {
+    let liquidity = 1_000_000_000_000u64;
+    // the DEX holds more than the pool's reserves, the pair being removed
+    let mut world = emergency_world(PairState::Active, 1_000_000_000_000, 1_000_000_000_000, &[], &[]);
+    world.account(LPS[0]).nonce(1).esdt_nft_balance(POSITION_TOKEN, 1, 1, ());
+
+    let mut reserves = (0u64, 0u64);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.pair(0).clear();
+        sc.token_reserves().clear();
+        let mut pool = cl_pool(1, PairState::Inactive, liquidity, 0, 0);
+        let (reserve_token, reserve_base) = sc.get_cl_amounts_for_liquidity(
+            &pool.sqrt_price,
+            &sc.get_sqrt_price_at_tick(-2_000),
+            &sc.get_sqrt_price_at_tick(2_000),
+            &BigUint::from(liquidity),
+            false,
+        );
+        pool.reserve_token = reserve_token.clone();
+        pool.reserve_base = reserve_base.clone();
+        sc.update_cl_tick(&pool, -2_000, &BigUint::from(liquidity), true, true);
+        sc.update_cl_tick(&pool, 2_000, &BigUint::from(liquidity), false, true);
+        sc.cl_position(1, 1).set(ClPosition {
+            pool_id: 1,
+            nonce: 1,
+            tick_lower: -2_000,
+            tick_upper: 2_000,
+            liquidity: BigUint::from(liquidity),
+            fee_growth_inside_token: BigUint::zero(),
+            fee_growth_inside_base: BigUint::zero(),
+        });
+        sc.increase_token_reserve(&pool.token, &reserve_token);
+        sc.increase_token_reserve(&pool.base_token, &reserve_base);
+        sc.cl_pool(1).set(pool);
+        sc.last_pair_id().set(2);
+        reserves = (reserve_token.to_u64().unwrap(), reserve_base.to_u64().unwrap());
+    });
+
+    world
+        .tx()
+        .from(LPS[0])
+        .to(DEX)
+        .esdt(TestEsdtTransfer(POSITION_TOKEN, 1, 1))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.remove_cl_position();
+        });
+
+    world
+        .check_account(LPS[0])
+        .esdt_balance(TOKEN, reserves.0)
+        .esdt_balance(BASE_TOKEN, reserves.1);
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        let pool = sc.cl_pool(1).get();
+        assert_eq!(pool.liquidity, 0u64);
+        assert!(sc.cl_position(1, 1).is_empty());
+        assert!(sc.token_reserves().is_empty());
+    });
+}
+
+// a farm of the pair holding 1,000 LP tokens, staked or locked, and paying 10 reward tokens per block, 10 blocks later
+fn farm_world(staked: u64, weight: u64) -> ScenarioWorld {
+    let mut world = emergency_world(
+        PairState::Active,
+        1_000_000,
+        1_000_000,
+        &[],
+        &[(LP_TOKEN, 1_000), (REWARD_TOKEN, 1_000)],
+    );
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.pair(0).update(|pair| pair.lp_supply = BigUint::from(1_000u64));
+        sc.farm(0).set(Farm {
+            pair_id: 0,
+            lp_token: LP_TOKEN.to_token_identifier(),
+            reward_token: REWARD_TOKEN.to_token_identifier(),
+            reward_per_block: BigUint::from(10u64),
+            reward_reserve: BigUint::from(1_000u64),
+            rewards_unclaimed: BigUint::zero(),
+            total_staked: BigUint::from(staked),
+            total_weight: BigUint::from(weight),
+            reward_per_share: BigUint::zero(),
+            last_reward_block: 0,
+        });
+        sc.increase_token_reserve(&LP_TOKEN.to_token_identifier(), &BigUint::from(1_000u64));
+        sc.increase_token_reserve(&REWARD_TOKEN.to_token_identifier(), &BigUint::from(1_000u64));
+    });
+    world.current_block().block_nonce(10).block_timestamp(1_000);
+
+    world
+}
+
+#[test]
+fn emergency_unstakes_farm_positions() {
+    let mut world = farm_world(1_000, 1_000);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.farm_position(0, &LPS[0].to_managed_address()).set(FarmPosition {
+            staked: BigUint::from(1_000u64),
+            reward_per_share: BigUint::zero(),
+            pending_rewards: BigUint::zero(),
+        });
+    });
+
+    world.tx().from(LPS[0]).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.unstake_lp(0, BigUint::from(1_000u64));
+    });
+
+    world
+        .check_account(LPS[0])
+        .esdt_balance(LP_TOKEN, 1_000u64)
+        .esdt_balance(REWARD_TOKEN, 100u64);
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        assert_eq!(sc.farm(0).get().total_staked, 0u64);
+    });
+}
+
+// the position is still locked for a long time, but emergency unlocks cost no penalty
+#[test]
+fn emergency_unlocks_lock_positions_without_penalty() {
+    let mut world = farm_world(0, 2_000);
+    world.account(LPS[0]).nonce(1).esdt_nft_balance(LOCK_TOKEN, 1, 1, ());
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.lock_token().set(LOCK_TOKEN.to_token_identifier());
+        sc.lock_pool(0).set(LockPool {
+            total_locked: BigUint::from(1_000u64),
+            total_weight: BigUint::from(2_000u64),
+            penalty_per_weight: BigUint::zero(),
+        });
+        sc.lock_position(1).set(LockPosition {
+            pair_id: 0,
+            amount: BigUint::from(1_000u64),
+            weight: BigUint::from(2_000u64),
+            lock_time: 0,
+            unlock_time: 1_000_000,
+            reward_per_share: BigUint::zero(),
+            penalty_per_weight: BigUint::zero(),
+        });
+    });
+
+    world
+        .tx()
+        .from(LPS[0])
+        .to(DEX)
+        .esdt(TestEsdtTransfer(LOCK_TOKEN, 1, 1))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.unlock_lp();
+        });
+
+    world
+        .check_account(LPS[0])
+        .esdt_balance(LP_TOKEN, 1_000u64)
+        .esdt_balance(REWARD_TOKEN, 100u64);
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        assert!(sc.lock_position(1).is_empty());
+        assert_eq!(sc.get_lock_pool(0).total_locked, 0u64);
+    });
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::{pair, world, BASE_TOKEN, DEX, LP_TOKEN, OWNER, TOKEN};
+use tfn_test_dex::{
+    common::{
+        config::{ConfigModule, LegacyPair, PairState},
+        consts::STORAGE_VERSION,
+    },
+    reserves::ReservesModule,
+    TFNTestDEXContract,
+};
+
+#[test]
+fn upgrade_migrates_legacy_pairs() {
+    let mut world = world();
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.legacy_pair(0).set(LegacyPair {
+            id: 0,
+            owner: OWNER.to_managed_address(),
+            state: PairState::Active,
+            token: TOKEN.to_token_identifier(),
+            base_token: BASE_TOKEN.to_token_identifier(),
+            lp_token: LP_TOKEN.to_token_identifier(),
+            lp_supply: BigUint::from(1_000_000u64),
+            lp_fee: 30,
+            owner_fee: 10,
+            liquidity_token: BigUint::from(2_000_000u64),
+            liquidity_base: BigUint::from(500_000u64),
+        });
+        sc.last_pair_id().set(1);
+    });
+
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.upgrade();
+    });
+
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        let mut expected = pair(PairState::Active, 2_000_000, 500_000, 1_000_000);
+        let migrated = sc.pair(0).get();
+        expected.amp = migrated.amp;
+        assert_eq!(migrated, expected);
+        assert_eq!(sc.storage_version().get(), STORAGE_VERSION);
+        assert_eq!(sc.token_reserves().get(&TOKEN.to_token_identifier()), Some(BigUint::from(2_000_000u64)));
+    });
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::{message, set_pair, world, DEX, OWNER};
+use tfn_test_dex::{
+    common::{
+        config::{ConfigModule, PairState, State},
+        errors::ERROR_PAIR_STATE_FORCED,
+    },
+    TFNTestDEXContract,
+};
+
+// an active DEX holding a pair in the given state, forced there by the subscriber
+fn forced_pair_world(pair_state: PairState) -> ScenarioWorld {
+    let mut world = world();
+    set_pair(&mut world, State::Active, pair_state, 1_000_000, 1_000_000, 1_000_000);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.pair_state_forced(0).set(true);
+    });
+
+    world
+}
+
+#[test]
+fn owner_cannot_reactivate_force_paused_pair() {
+    let mut world = forced_pair_world(PairState::ActiveNoSwap);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectMessage(message(ERROR_PAIR_STATE_FORCED)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.set_pair_active(0);
+        });
+
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        assert!(sc.pair(0).get().state == PairState::ActiveNoSwap);
+    });
+}
+
+#[test]
+fn owner_cannot_change_forced_state() {
+    for setter in 0..3 {
+        let mut world = forced_pair_world(PairState::Active);
+        world
+            .tx()
+            .from(OWNER)
+            .to(DEX)
+            .returns(ExpectMessage(message(ERROR_PAIR_STATE_FORCED)))
+            .whitebox(tfn_test_dex::contract_obj, |sc| match setter {
+                0 => sc.set_pair_active_no_swap(0),
+                1 => sc.set_pair_inactive(0),
+                _ => sc.set_pair_delisted(0),
+            });
+    }
+}
@@ -0,0 +1,204 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::{cl_pool, message, set_pair, world_with, BASE_TOKEN, DEX, OWNER, TOKEN};
+use tfn_test_dex::{
+    common::{
+        config::{ConfigModule, DynamicFee, FeeRange, PairState, State},
+        errors::*,
+    },
+    concentrated::ConcentratedModule,
+    dynamic_fee::DynamicFeeModule,
+    liquidity::LiquidityModule,
+    pair_policy::PairPolicyModule,
+    TFNTestDEXContract,
+};
+
+// an active DEX holding a pair in the given state, with the given LP supply, owner holding both tokens
+fn policy_world(pair_state: PairState, lp_supply: u64) -> ScenarioWorld {
+    let mut world = world_with(&[(TOKEN, lp_supply), (BASE_TOKEN, lp_supply)], &[]);
+    world
+        .account(OWNER)
+        .nonce(1)
+        .esdt_balance(TOKEN, 1_000_000u64)
+        .esdt_balance(BASE_TOKEN, 1_000_000u64);
+    set_pair(&mut world, State::Active, pair_state, lp_supply, lp_supply, lp_supply);
+
+    world
+}
+
+fn add_liquidity(world: &mut ScenarioWorld, amount: u64, error: &[u8]) {
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(TOKEN, 0, amount))
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, amount))
+        .returns(ExpectMessage(message(error)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.add_liquidity();
+        });
+}
+
+#[test]
+fn funded_pairs_cannot_be_forced_pending_and_rejected() {
+    let mut world = policy_world(PairState::Active, 1_000_000);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectMessage(message(ERROR_PAIR_PENDING_APPROVAL)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.force_pair_state(0, PairState::PendingApproval);
+        });
+
+    // even if a funded pair ended up pending approval, rejecting it would leave its LP tokens worthless
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.pair(0).update(|pair| pair.state = PairState::PendingApproval);
+    });
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectMessage(message(ERROR_PAIR_HAS_LIQUIDITY)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.reject_pair(0);
+        });
+
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        assert_eq!(sc.pair(0).get().lp_supply, 1_000_000u64);
+    });
+}
+
+#[test]
+fn funded_cl_pools_cannot_be_rejected() {
+    let mut world = policy_world(PairState::Active, 1_000_000);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.cl_pool(1).set(cl_pool(1, PairState::PendingApproval, 0, 0, 0));
+        sc.cl_pool_ticks(1).insert(-100);
+        sc.last_pair_id().set(2);
+    });
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectMessage(message(ERROR_PAIR_HAS_LIQUIDITY)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.reject_pair(1);
+        });
+}
+
+#[test]
+fn pending_pairs_cannot_receive_liquidity() {
+    let mut world = policy_world(PairState::PendingApproval, 0);
+    add_liquidity(&mut world, 1_000, ERROR_PAIR_PENDING_APPROVAL);
+}
+
+#[test]
+fn initial_liquidity_below_minimum_is_rejected() {
+    let mut world = policy_world(PairState::ActiveNoSwap, 0);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.min_initial_liquidity(&BASE_TOKEN.to_token_identifier()).set(BigUint::from(10_000u64));
+    });
+
+    add_liquidity(&mut world, 1_000, ERROR_INITIAL_LIQUIDITY_TOO_LOW);
+}
+
+// pairs and concentrated liquidity pools both count towards the owner's limit
+#[test]
+fn pair_limit_counts_cl_pools() {
+    let mut world = policy_world(PairState::Active, 1_000_000);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.cl_pool(1).set(cl_pool(1, PairState::Active, 0, 0, 0));
+        sc.last_pair_id().set(2);
+        sc.max_pairs_per_owner().set(2);
+    });
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectMessage(message(ERROR_TOO_MANY_PAIRS)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.check_pair_creation_policy(&OWNER.to_managed_address(), 30, 10);
+        });
+}
+
+#[test]
+fn fees_outside_the_tier_range_are_rejected() {
+    let mut world = policy_world(PairState::Active, 1_000_000);
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.student_tier(&OWNER.to_managed_address()).set(1);
+        sc.tier_fee_range(1).set(FeeRange {
+            min_lp_fee: 10,
+            max_lp_fee: 50,
+            min_owner_fee: 0,
+            max_owner_fee: 20,
+        });
+        sc.check_pair_creation_policy(&OWNER.to_managed_address(), 50, 20);
+    });
+
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectMessage(message(ERROR_FEES_OUT_OF_RANGE)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.check_pair_creation_policy(&OWNER.to_managed_address(), 30, 30);
+        });
+}
+
+// a range allowing fees that add up to 100% would let pairs take the whole swap amount
+#[test]
+fn tier_fee_range_must_stay_below_100_percent() {
+    let mut world = policy_world(PairState::Active, 1_000_000);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectMessage(message(ERROR_WRONG_FEE_RANGE)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.set_tier_fee_range(
+                1,
+                FeeRange {
+                    min_lp_fee: 0,
+                    max_lp_fee: 9_000,
+                    min_owner_fee: 0,
+                    max_owner_fee: 1_000,
+                },
+            );
+        });
+}
+
+// with a dynamic fee the LP fee can rise up to its maximum, which should leave room for the owner fee
+#[test]
+fn pair_fees_must_stay_below_100_percent() {
+    let mut world = policy_world(PairState::Active, 1_000_000);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectMessage(message(ERROR_WRONG_FEES)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.change_pair_fees(0, 9_000, 1_000);
+        });
+
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        sc.dynamic_fee(0).set(DynamicFee {
+            min_fee: 30,
+            max_fee: 9_000,
+            window: 600,
+            sensitivity: 1,
+        });
+    });
+    world
+        .tx()
+        .from(OWNER)
+        .to(DEX)
+        .returns(ExpectMessage(message(ERROR_WRONG_FEES)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.change_pair_fees(0, 30, 1_000);
+        });
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::{message, pair, world, BASE_TOKEN, DEX, OWNER, TOKEN};
+use tfn_test_dex::{
+    common::{
+        config::{self, AmpRamp, ConfigModule, PairState, State},
+        errors::ERROR_INVARIANT_VIOLATED,
+    },
+    reserves::ReservesModule,
+    swap::SwapModule,
+};
+use tfn_test_dex_core::{
+    curve::check_swap_invariant,
+    testing::{Rng, ITERATIONS},
+    Curve, CurveParams,
+};
+
+const MAX_FEE: u64 = 1_000;
+
+fn constant_product() -> CurveParams {
+    CurveParams {
+        curve: Curve::ConstantProduct,
+        amp: 0,
+        weight_in: 5_000,
+        weight_out: 5_000,
+        rate_in: 1,
+        rate_out: 1,
+    }
+}
+
+// every curve is checked against its invariant, the constant product one also directly
+fn assert_invariant(
+    liquidity_in: &BigUint<StaticApi>,
+    liquidity_out: &BigUint<StaticApi>,
+    new_liquidity_in: &BigUint<StaticApi>,
+    new_liquidity_out: &BigUint<StaticApi>,
+    params: &CurveParams,
+) {
+    if params.curve == Curve::ConstantProduct {
+        assert!(new_liquidity_in * new_liquidity_out >= liquidity_in * liquidity_out);
+    }
+    assert!(check_swap_invariant(liquidity_in, liquidity_out, new_liquidity_in, new_liquidity_out, params));
+}
+
+fn fuzz_swaps(params: CurveParams, seed: u64) {
+    let mut world = world();
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        let mut rng = Rng(seed);
+        for _ in 0..ITERATIONS {
+            let liquidity_in = BigUint::from(rng.range(1_000, 1_000_000_000_000_000_000));
+            let liquidity_out = BigUint::from(rng.range(1_000, 1_000_000_000_000_000_000));
+            let lp_fee = rng.range(0, MAX_FEE);
+            let owner_fee = rng.range(0, MAX_FEE);
+
+            for fee_in in [true, false] {
+                let amount_in = BigUint::from(rng.range(1, 1_000_000_000_000_000_000));
+                let result = sc.do_swap_fixed_input(
+                    &amount_in,
+                    &liquidity_in,
+                    &liquidity_out,
+                    fee_in,
+                    lp_fee,
+                    owner_fee,
+                    &params,
+                );
+                assert_invariant(&liquidity_in, &liquidity_out, &result.new_liquidity_in, &result.new_liquidity_out, &params);
+
+                // leave some room for the fees taken from the output
+                let max_out = &liquidity_out / 2u64;
+                if max_out == 0 {
+                    continue;
+                }
+                let amount_out = BigUint::from(rng.next_u64()) % &max_out + 1u64;
+                let result = sc.do_swap_fixed_output(
+                    &amount_out,
+                    &liquidity_in,
+                    &liquidity_out,
+                    fee_in,
+                    lp_fee,
+                    owner_fee,
+                    &params,
+                );
+                assert_invariant(&liquidity_in, &liquidity_out, &result.new_liquidity_in, &result.new_liquidity_out, &params);
+            }
+        }
+    });
+}
+
+#[test]
+fn constant_product_swaps_keep_invariant() {
+    fuzz_swaps(constant_product(), 0x5eed_0001);
+}
+
+#[test]
+fn stable_swaps_keep_invariant() {
+    fuzz_swaps(
+        CurveParams {
+            curve: Curve::StableSwap,
+            amp: 100,
+            weight_in: 5_000,
+            weight_out: 5_000,
+            rate_in: 1,
+            rate_out: 1,
+        },
+        0x5eed_0002,
+    );
+}
+
+#[test]
+fn weighted_swaps_keep_invariant() {
+    fuzz_swaps(
+        CurveParams {
+            curve: Curve::Weighted,
+            amp: 0,
+            weight_in: 8_000,
+            weight_out: 2_000,
+            rate_in: 1,
+            rate_out: 1,
+        },
+        0x5eed_0003,
+    );
+}
+
+#[test]
+fn decreasing_k_is_rejected() {
+    assert!(!check_swap_invariant(
+        &BigUint::<StaticApi>::from(1_000u64),
+        &BigUint::<StaticApi>::from(1_000u64),
+        &BigUint::<StaticApi>::from(1_100u64),
+        &BigUint::<StaticApi>::from(900u64),
+        &constant_product(),
+    ));
+}
+
+// for a stable pair this imbalanced, the output of a tiny swap would lower the approximated D,
+// which the swap endpoint rejects
+#[test]
+fn swap_endpoint_rejects_invariant_violation() {
+    let liquidity_token = 6_030_000u64;
+    let liquidity_base = 443_000_000_000_000_000u64;
+    let user = TestAddress::new("user");
+    let mut world = world();
+    world
+        .account(DEX)
+        .nonce(1)
+        .code(common::CODE_PATH)
+        .owner(OWNER)
+        .esdt_balance(TOKEN, liquidity_token)
+        .esdt_balance(BASE_TOKEN, liquidity_base);
+    world.account(user).nonce(1).esdt_balance(BASE_TOKEN, 1_000u64);
+
+    world.tx().from(OWNER).to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        let mut pair = pair(PairState::Active, liquidity_token, liquidity_base, liquidity_token);
+        pair.curve = config::Curve::StableSwap;
+        pair.amp = AmpRamp {
+            initial_amp: 66_013,
+            future_amp: 66_013,
+            initial_time: 0,
+            future_time: 0,
+        };
+        sc.increase_token_reserve(&pair.token, &pair.liquidity_token);
+        sc.increase_token_reserve(&pair.base_token, &pair.liquidity_base);
+        sc.pair(0).set(pair);
+        sc.last_pair_id().set(1);
+        sc.state().set(State::Active);
+    });
+
+    world
+        .tx()
+        .from(user)
+        .to(DEX)
+        .esdt(TestEsdtTransfer(BASE_TOKEN, 0, 712u64))
+        .returns(ExpectMessage(message(ERROR_INVARIANT_VIOLATED)))
+        .whitebox(tfn_test_dex::contract_obj, |sc| {
+            sc.swap_fixed_input(EgldOrEsdtTokenIdentifier::esdt(TOKEN.to_token_identifier()), BigUint::zero(), OptionalValue::None);
+        });
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use multiversx_sc_scenario::imports::*;
+
+use common::{set_pair, world_with, BASE_TOKEN, DEX, OWNER, TOKEN};
+use tfn_test_dex::{
+    common::config::{ConfigModule, PairState, State},
+    swap::SwapModule,
+};
+use tfn_test_dex_core::testing::Rng;
+
+const USER: TestAddress = TestAddress::new("user");
+const USER_BALANCE: u64 = 1_000_000_000_000_000_000;
+
+// each case deploys its own world, so fewer of them are run
+const WORLD_ITERATIONS: usize = 20;
+
+// an active pair with the given reserves, held by the DEX, and a user holding both tokens
+fn pair_world(liquidity_token: u64, liquidity_base: u64) -> ScenarioWorld {
+    let mut world = world_with(&[(TOKEN, liquidity_token), (BASE_TOKEN, liquidity_base)], &[]);
+    world
+        .account(USER)
+        .nonce(1)
+        .esdt_balance(TOKEN, USER_BALANCE)
+        .esdt_balance(BASE_TOKEN, USER_BALANCE);
+
+    set_pair(&mut world, State::Active, PairState::Active, liquidity_token, liquidity_base, liquidity_base);
+
+    world
+}
+
+fn quote_amount_out(world: &mut ScenarioWorld, token_in: TestTokenIdentifier, token_out: TestTokenIdentifier, amount_in: u64) -> u64 {
+    let mut quoted = 0;
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        quoted = sc
+            .get_amount_out_view(&token_in.to_token_identifier(), &token_out.to_token_identifier(), BigUint::from(amount_in))
+            .to_u64()
+            .unwrap();
+    });
+
+    quoted
+}
+
+fn quote_amount_in(world: &mut ScenarioWorld, token_in: TestTokenIdentifier, token_out: TestTokenIdentifier, amount_out: u64) -> u64 {
+    let mut quoted = 0;
+    world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+        quoted = sc
+            .get_amount_in_view(&token_in.to_token_identifier(), &token_out.to_token_identifier(), BigUint::from(amount_out))
+            .to_u64()
+            .unwrap();
+    });
+
+    quoted
+}
+
+// the views are compared with what the swap endpoints actually transfer to and from the user
+#[test]
+fn views_match_swap_endpoints() {
+    let mut rng = Rng(0x5eed_3401);
+    for _ in 0..WORLD_ITERATIONS {
+        let liquidity_token = rng.range(1_000_000, 1_000_000_000_000_000);
+        let liquidity_base = rng.range(1_000_000, 1_000_000_000_000_000);
+        let mut world = pair_world(liquidity_token, liquidity_base);
+        let mut balances = [USER_BALANCE, USER_BALANCE];
+        let mut reserves = [liquidity_token, liquidity_base];
+
+        for (token_in, token_out, index_in) in [(TOKEN, BASE_TOKEN, 0), (BASE_TOKEN, TOKEN, 1)] {
+            let index_out = 1 - index_in;
+
+            let amount_in = rng.range(1, reserves[index_in] / 10);
+            let quoted_out = quote_amount_out(&mut world, token_in, token_out, amount_in);
+            world
+                .tx()
+                .from(USER)
+                .to(DEX)
+                .esdt(TestEsdtTransfer(token_in, 0, amount_in))
+                .whitebox(tfn_test_dex::contract_obj, |sc| {
+                    sc.swap_fixed_input(EgldOrEsdtTokenIdentifier::esdt(token_out.to_token_identifier()), BigUint::zero(), OptionalValue::None);
+                });
+            balances[index_in] -= amount_in;
+            balances[index_out] += quoted_out;
+            world.check_account(USER).esdt_balance(TOKEN, balances[0]).esdt_balance(BASE_TOKEN, balances[1]);
+            world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+                let pair = sc.pair(0).get();
+                reserves = [pair.liquidity_token.to_u64().unwrap(), pair.liquidity_base.to_u64().unwrap()];
+            });
+
+            // the payment is larger than needed, the unused part being refunded
+            let amount_out = rng.range(1, reserves[index_out] / 10);
+            let quoted_in = quote_amount_in(&mut world, token_in, token_out, amount_out);
+            world
+                .tx()
+                .from(USER)
+                .to(DEX)
+                .esdt(TestEsdtTransfer(token_in, 0, quoted_in * 2))
+                .whitebox(tfn_test_dex::contract_obj, |sc| {
+                    sc.swap_fixed_output(
+                        EgldOrEsdtTokenIdentifier::esdt(token_out.to_token_identifier()),
+                        BigUint::from(amount_out),
+                        OptionalValue::None,
+                    );
+                });
+            balances[index_in] -= quoted_in;
+            balances[index_out] += amount_out;
+            world.check_account(USER).esdt_balance(TOKEN, balances[0]).esdt_balance(BASE_TOKEN, balances[1]);
+            world.query().to(DEX).whitebox(tfn_test_dex::contract_obj, |sc| {
+                let pair = sc.pair(0).get();
+                reserves = [pair.liquidity_token.to_u64().unwrap(), pair.liquidity_base.to_u64().unwrap()];
+            });
+        }
+    }
+}